@@ -31,4 +31,124 @@ pub enum DexProgramError {
 
     #[msg("Overflow or underflow occured")]
     OverflowOrUnderflowOccurred,
+
+    #[msg("Signer is not authorized to perform this action")]
+    Unauthorized,
+
+    #[msg("Pool has no wrapped SOL leg to settle in native SOL")]
+    NativeSolNotSupported,
+
+    #[msg("Transaction deadline has passed")]
+    DeadlineExceeded,
+
+    #[msg("Operation is paused")]
+    OperationPaused,
+
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+
+    #[msg("Price impact is too high")]
+    PriceImpactTooHigh,
+
+    #[msg("Invalid pool parameter")]
+    InvalidPoolParameter,
+
+    #[msg("Trade size exceeds the pool limit")]
+    TradeSizeTooLarge,
+
+    #[msg("Pool vault balance is below the tracked reserves")]
+    VaultBalanceBelowReserves,
+
+    #[msg("Liquidity provider account still holds shares")]
+    LiquidityProviderNotEmpty,
+
+    #[msg("Liquidity pool still holds liquidity")]
+    PoolNotEmpty,
+
+    #[msg("Account cannot be migrated")]
+    InvalidAccountToMigrate,
+
+    #[msg("Account is already on the latest version")]
+    AccountAlreadyMigrated,
+
+    #[msg("Dex configuration has no admin, migrate it first")]
+    ConfigurationNotMigrated,
+
+    #[msg("Shares cannot be transferred to the same owner")]
+    InvalidShareRecipient,
+
+    #[msg("Shares exceed the delegated allowance")]
+    DelegatedSharesExceeded,
+
+    #[msg("Shares are locked")]
+    SharesLocked,
+
+    #[msg("Invalid lock duration")]
+    InvalidLock,
+
+    #[msg("No fees to claim")]
+    NoFeesToClaim,
+
+    #[msg("Invalid reward vault")]
+    InvalidRewardVault,
+
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+
+    #[msg("Governance tokens are locked")]
+    GovernanceTokensLocked,
+
+    #[msg("Vote allocation exceeds the available voting power")]
+    VotingPowerExceeded,
+
+    #[msg("Emissions already distributed for this epoch")]
+    EmissionsAlreadyDistributed,
+
+    #[msg("Epoch duration must be positive")]
+    InvalidEpochDuration,
+
+    #[msg("Nothing to harvest")]
+    NothingToHarvest,
+
+    #[msg("Rewards not paid in a pool token cannot be compounded by the vault")]
+    RewardNotCompoundable,
+
+    #[msg("Referral fee exceeds the configured maximum")]
+    ReferralFeeTooHigh,
+
+    #[msg("Invalid referrer accounts")]
+    InvalidReferrer,
+
+    #[msg("Order has expired")]
+    OrderExpired,
+
+    #[msg("Execution price is below the order limit price")]
+    LimitPriceNotReached,
+
+    #[msg("An order expiring at the same boundary slot is still unsettled")]
+    TwammBoundaryInUse,
+
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+
+    #[msg("The next DCA cycle is not due yet")]
+    DcaCycleNotDue,
+
+    #[msg("Not enough price history for the TWAP window")]
+    OracleUnavailable,
+
+    #[msg("Trigger price has not been reached")]
+    TriggerPriceNotReached,
+
+    #[msg("Invalid RFQ quote")]
+    InvalidRfqQuote,
+
+    #[msg("RFQ quote is not signed by the maker")]
+    InvalidQuoteSignature,
+
+    #[msg("A token account is required for this leg of the trade")]
+    MissingTokenAccount,
+
+    #[msg("Harvest adds fewer shares than the minimum")]
+    HarvestBelowMinimum,
 }
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+use crate::errors::DexProgramError;
+
+// Grows an account written with an older layout of `T` to `new_size`, topping
+// up its rent from the payer. The new bytes are zeroed so fields added after
+// the old layout deserialize to their zero value.
+//
+// Versioned accounts end in `reserved` padding that is zero on every account.
+// A new field that fits is carved out of it, so existing accounts keep their
+// size and only need their version bumped. Only fields larger than what is
+// left grow the account, and then the padding keeps its size.
+pub fn realloc_account<'info, T: Discriminator>(
+    account: &AccountInfo<'info>,
+    new_size: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if !account.owner.eq(&crate::ID) {
+        return err!(DexProgramError::InvalidAccountToMigrate);
+    }
+
+    {
+        let data = account.try_borrow_data()?;
+
+        if data.len() < 8 || data[..8] != T::DISCRIMINATOR {
+            return err!(DexProgramError::InvalidAccountToMigrate);
+        }
+    }
+
+    if account.data_len() >= new_size {
+        return Ok(());
+    }
+
+    let rent_required = Rent::get()?
+        .minimum_balance(new_size)
+        .saturating_sub(account.lamports());
+
+    if rent_required > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent_required,
+        )?;
+    }
+
+    account.realloc(new_size, true)?;
+
+    Ok(())
+}
@@ -1,2 +1,6 @@
+pub mod account;
 pub mod math;
+pub mod transfer;
+pub use account::*;
 pub use math::*;
+pub use transfer::*;
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+// Moves tokens out of an account the signer owns, to any token account
+pub fn transfer_from_user<'info>(
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+    authority: &Signer<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+                authority: authority.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+// Pays the signer's lamports into a wrapped SOL account. Lamports sent to it
+// only count towards its token balance once the token program syncs it.
+pub fn wrap_sol_from_user<'info>(
+    from: &Signer<'info>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+    system_program: &Program<'info, System>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: from.to_account_info(),
+                to: to.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    token::sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        token::SyncNative {
+            account: to.to_account_info(),
+        },
+    ))
+}
@@ -0,0 +1,57 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+
+    // Anyone may clean up an expired order, the funds always go back to its owner
+    if !ctx.accounts.authority.key().eq(&order.owner)
+        && !order.is_expired(Clock::get()?.unix_timestamp)
+    {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    order.refund_and_close_escrow(
+        &ctx.accounts.escrow,
+        0_u64,
+        Some(&ctx.accounts.owner_token_account),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(
+        mut,
+        seeds = [Order::SEED_PREFIX.as_bytes(), order.pool.as_ref(), owner.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        mut,
+        seeds = [Order::ESCROW_SEED_PREFIX.as_bytes(), order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = order.input_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
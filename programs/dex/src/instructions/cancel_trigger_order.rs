@@ -0,0 +1,48 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+pub fn cancel_trigger_order(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+    let order = &ctx.accounts.trigger_order;
+
+    order.refund_and_close_escrow(
+        &ctx.accounts.escrow,
+        0_u64,
+        Some(&ctx.accounts.owner_token_account),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelTriggerOrder<'info> {
+    #[account(
+        mut,
+        seeds = [TriggerOrder::SEED_PREFIX.as_bytes(), trigger_order.pool.as_ref(), owner.key().as_ref(), &trigger_order.order_id.to_le_bytes()],
+        bump = trigger_order.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub trigger_order: Box<Account<'info, TriggerOrder>>,
+
+    #[account(
+        mut,
+        seeds = [TriggerOrder::ESCROW_SEED_PREFIX.as_bytes(), trigger_order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = trigger_order.input_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
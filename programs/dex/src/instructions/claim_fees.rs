@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{spl_token::native_mint, Mint, Token, TokenAccount},
+};
+
+use crate::{
+    errors::DexProgramError,
+    state::{
+        native_sol_legs, settlement_account, DexConfiguration, FeeAmounts, LiquidityPool,
+        LiquidityPoolAccount, LiquidityProvider,
+    },
+};
+
+pub fn claim_fees(ctx: Context<ClaimFees>, native_sol: bool) -> Result<FeeAmounts> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Fees earned by the long-term orders due by now are part of the claim
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    // A native SOL leg settles through the temporary wrapped SOL account, the
+    // user needs no token account for it
+    let (native_sol_account_one, native_sol_account_two) = native_sol_legs(
+        native_sol,
+        ctx.accounts.native_sol_account.as_deref_mut(),
+        &ctx.accounts.mint_token_one,
+    )?;
+
+    let token_one_accounts = (
+        &mut *ctx.accounts.mint_token_one,
+        &mut *ctx.accounts.pool_token_account_one,
+        settlement_account(
+            native_sol_account_one,
+            ctx.accounts.user_token_account_one.as_deref_mut(),
+        )?,
+    );
+
+    let token_two_accounts = (
+        &mut *ctx.accounts.mint_token_two,
+        &mut *ctx.accounts.pool_token_account_two,
+        settlement_account(
+            native_sol_account_two,
+            ctx.accounts.user_token_account_two.as_deref_mut(),
+        )?,
+    );
+
+    let fee_amounts = pool.claim_fees(
+        token_one_accounts,
+        token_two_accounts,
+        native_sol,
+        &mut ctx.accounts.liquidity_provider_account,
+        &ctx.accounts.token_program,
+    )?;
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    // Closing the temporary wrapped SOL account pays out its lamports, the rent
+    // and any unwrapped SOL, to the user
+    if let Some(native_sol_account) = ctx.accounts.native_sol_account.as_ref() {
+        pool.close_pool_token_account(
+            native_sol_account,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    Ok(fee_amounts)
+}
+
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = user,
+    )]
+    pub user_token_account_one: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = user,
+    )]
+    pub user_token_account_two: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Temporary wrapped SOL account a native SOL leg settles through. It is owned
+    // by the pool and closed again before the instruction ends.
+    #[account(
+        init,
+        payer = user,
+        seeds = [LiquidityPool::NATIVE_SOL_SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool,
+    )]
+    pub native_sol_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
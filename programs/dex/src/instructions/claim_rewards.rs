@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityPoolAccount, LiquidityProvider, RewardVault},
+};
+
+pub fn claim_rewards(ctx: Context<ClaimRewards>, reward_index: u8) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+
+    let index = pool.reward_index(reward_index, ctx.accounts.reward_mint.key())?;
+
+    pool.update_rewards(Clock::get()?.unix_timestamp)?;
+    pool.accrue_rewards(liquidity_provider_account)?;
+
+    let rewards = liquidity_provider_account.rewards_owed[index];
+
+    if rewards == 0 {
+        return err!(DexProgramError::NoRewardsToClaim);
+    }
+
+    liquidity_provider_account.rewards_owed[index] = 0_u64;
+    pool.release_rewards(index, rewards)?;
+
+    pool.transfer_token_from_pool(
+        &ctx.accounts.reward_vault_token_account,
+        &ctx.accounts.user_reward_token_account,
+        rewards,
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(rewards)
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [RewardVault::SEED_PREFIX.as_bytes(), pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_reward_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
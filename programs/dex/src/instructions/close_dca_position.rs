@@ -0,0 +1,57 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+// Stops the recurring buy and returns whatever is left in the escrow
+pub fn close_dca_position(ctx: Context<CloseDcaPosition>) -> Result<()> {
+    let position = &ctx.accounts.dca_position;
+    let remaining_amount = ctx.accounts.escrow.amount;
+
+    if remaining_amount > 0 {
+        position.transfer_from_escrow(
+            &ctx.accounts.escrow,
+            &ctx.accounts.owner_token_account,
+            remaining_amount,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    position.close_escrow(
+        &ctx.accounts.escrow,
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseDcaPosition<'info> {
+    #[account(
+        mut,
+        seeds = [DcaPosition::SEED_PREFIX.as_bytes(), dca_position.pool.as_ref(), owner.key().as_ref(), &dca_position.position_id.to_le_bytes()],
+        bump = dca_position.bump,
+        has_one = owner,
+        close = owner,
+    )]
+    pub dca_position: Box<Account<'info, DcaPosition>>,
+
+    #[account(
+        mut,
+        seeds = [DcaPosition::ESCROW_SEED_PREFIX.as_bytes(), dca_position.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = dca_position.input_mint,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,30 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn close_liquidity_provider(_ctx: Context<CloseLiquidityProvider>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseLiquidityProvider<'info> {
+    /// CHECK: Only used to derive the liquidity provider address, the pool may
+    /// already have been closed
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+        has_one = payer,
+        constraint = liquidity_provider_account.shares == 0 @ DexProgramError::LiquidityProviderNotEmpty,
+        constraint = liquidity_provider_account.fees_owed_one == 0 && liquidity_provider_account.fees_owed_two == 0 @ DexProgramError::LiquidityProviderNotEmpty,
+        constraint = liquidity_provider_account.rewards_owed.iter().all(|owed| *owed == 0) @ DexProgramError::LiquidityProviderNotEmpty,
+        close = payer,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+
+    pub user: Signer<'info>,
+}
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityPoolAccount},
+};
+
+pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+    let pool = &ctx.accounts.pool;
+
+    // Reward vaults are closed one by one beforehand, each once its rewards are
+    // all claimed.
+    if pool.total_supply != 0
+        || pool.fees_owed_one != 0
+        || pool.fees_owed_two != 0
+        || pool.has_reward_vaults()
+        || pool.twamm.has_open_orders()
+    {
+        return err!(DexProgramError::PoolNotEmpty);
+    }
+
+    // Nothing left in the vaults is owed to anyone, only rounding dust and
+    // donations remain. Sweep them to the payer so a stray transfer can't keep
+    // the pool from being closed.
+    for (pool_token_account, payer_token_account) in [
+        (
+            &ctx.accounts.pool_token_account_one,
+            &ctx.accounts.payer_token_account_one,
+        ),
+        (
+            &ctx.accounts.pool_token_account_two,
+            &ctx.accounts.payer_token_account_two,
+        ),
+    ] {
+        if pool_token_account.amount > 0 {
+            pool.transfer_token_from_pool(
+                pool_token_account,
+                payer_token_account,
+                pool_token_account.amount,
+                &ctx.accounts.token_program,
+            )?;
+        }
+    }
+
+    pool.close_pool_token_account(
+        &ctx.accounts.pool_token_account_one,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    pool.close_pool_token_account(
+        &ctx.accounts.pool_token_account_two,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        has_one = payer,
+        close = payer,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = payer,
+    )]
+    pub payer_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = payer,
+    )]
+    pub payer_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityPoolAccount, RewardVault},
+};
+
+pub fn close_reward_vault(ctx: Context<CloseRewardVault>, reward_index: u8) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let index = pool.reward_index(reward_index, ctx.accounts.reward_mint.key())?;
+
+    // Only an emptied pool gives up its reward vaults, and only once every
+    // reward that was emitted has been claimed and nothing is left to emit.
+    if pool.total_supply != 0
+        || pool.rewards_owed[index] != 0
+        || pool.rewards[index].undistributed != 0
+    {
+        return err!(DexProgramError::PoolNotEmpty);
+    }
+
+    // Whatever is left is rounding dust or a donation
+    let reward_vault_token_account = &ctx.accounts.reward_vault_token_account;
+
+    if reward_vault_token_account.amount > 0 {
+        pool.transfer_token_from_pool(
+            reward_vault_token_account,
+            &ctx.accounts.payer_reward_token_account,
+            reward_vault_token_account.amount,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    pool.close_pool_token_account(
+        reward_vault_token_account,
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    pool.rewards[index] = RewardVault::default();
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseRewardVault<'info> {
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump,
+        has_one = payer,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [RewardVault::SEED_PREFIX.as_bytes(), pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = reward_mint,
+        associated_token::authority = payer,
+    )]
+    pub payer_reward_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
@@ -0,0 +1,40 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn configure_circuit_breaker(
+    ctx: Context<ConfigureCircuitBreaker>,
+    max_slot_price_movement_bps: u16,
+    max_window_price_movement_bps: u16,
+    price_window_slots: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if max_window_price_movement_bps > 0 && price_window_slots == 0 {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    pool.max_slot_price_movement_bps = max_slot_price_movement_bps;
+    pool.max_window_price_movement_bps = max_window_price_movement_bps;
+    pool.price_window_slots = price_window_slots;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureCircuitBreaker<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,43 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn configure_dynamic_fee(
+    ctx: Context<ConfigureDynamicFee>,
+    enabled: bool,
+    min_fee: f64,
+    max_fee: f64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if !(0_f64..=100_f64).contains(&min_fee)
+        || !(0_f64..=100_f64).contains(&max_fee)
+        || min_fee > max_fee
+    {
+        return err!(DexProgramError::InvalidFee);
+    }
+
+    pool.dynamic_fee_enabled = enabled;
+    pool.min_fee = min_fee;
+    pool.max_fee = max_fee;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureDynamicFee<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,34 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn configure_fee_discounts(
+    ctx: Context<ConfigureFeeDiscounts>,
+    tiers: Vec<FeeDiscountTier>,
+) -> Result<()> {
+    if tiers.len() > DexConfiguration::MAX_FEE_DISCOUNT_TIERS
+        || tiers.iter().any(|tier| tier.discount_bps > 10_000)
+    {
+        return err!(DexProgramError::InvalidFee);
+    }
+
+    let mut fee_discount_tiers =
+        [FeeDiscountTier::default(); DexConfiguration::MAX_FEE_DISCOUNT_TIERS];
+    fee_discount_tiers[..tiers.len()].copy_from_slice(&tiers);
+
+    ctx.accounts.dex_configuration_account.fee_discount_tiers = fee_discount_tiers;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureFeeDiscounts<'info> {
+    #[account(
+        mut,
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    pub admin: Signer<'info>,
+}
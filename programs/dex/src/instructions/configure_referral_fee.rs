@@ -0,0 +1,28 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn configure_referral_fee(
+    ctx: Context<ConfigureReferralFee>,
+    max_referral_fee_bps: u16,
+) -> Result<()> {
+    if max_referral_fee_bps > 10_000 {
+        return err!(DexProgramError::InvalidFee);
+    }
+
+    ctx.accounts.dex_configuration_account.max_referral_fee_bps = max_referral_fee_bps;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureReferralFee<'info> {
+    #[account(
+        mut,
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,38 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn configure_trade_limits(
+    ctx: Context<ConfigureTradeLimits>,
+    max_trade_size_bps: u16,
+    max_price_impact_bps: u16,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if max_trade_size_bps > 10_000 {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    pool.max_trade_size_bps = max_trade_size_bps;
+    pool.max_price_impact_bps = max_price_impact_bps;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ConfigureTradeLimits<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,66 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+pub fn create_compounding_vault(ctx: Context<CreateCompoundingVault>) -> Result<()> {
+    // The vault can only compound what it earns in the pool tokens, rewards paid
+    // in any other token would pile up in its position without an owner.
+    if ctx.accounts.pool.has_foreign_rewards() {
+        return err!(DexProgramError::RewardNotCompoundable);
+    }
+
+    ctx.accounts.vault.set_inner(CompoundingVault::new(
+        ctx.accounts.pool.key(),
+        ctx.accounts.share_mint.key(),
+        ctx.bumps.vault,
+    ));
+
+    let vault_liquidity_provider_account = &mut ctx.accounts.vault_liquidity_provider_account;
+    vault_liquidity_provider_account.payer = ctx.accounts.payer.key();
+    vault_liquidity_provider_account.version = LiquidityProvider::VERSION;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateCompoundingVault<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = CompoundingVault::ACCOUNT_SIZE,
+        seeds = [CompoundingVault::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump,
+    )]
+    pub vault: Box<Account<'info, CompoundingVault>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [CompoundingVault::SHARE_MINT_SEED_PREFIX.as_bytes(), vault.key().as_ref()],
+        bump,
+        mint::decimals = CompoundingVault::SHARE_DECIMALS,
+        mint::authority = vault,
+    )]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = LiquidityProvider::ACCOUNT_SIZE,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,53 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn create_gauge(ctx: Context<CreateGauge>) -> Result<()> {
+    // Votes cast from now on are tallied for the current epoch, which is the first
+    // one the gauge can receive emissions for.
+    let epoch = ctx
+        .accounts
+        .gauge_controller
+        .epoch_at(Clock::get()?.unix_timestamp);
+
+    ctx.accounts
+        .gauge
+        .set_inner(Gauge::new(ctx.accounts.pool.key(), epoch, ctx.bumps.gauge));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateGauge<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = Gauge::ACCOUNT_SIZE,
+        seeds = [Gauge::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump,
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -12,6 +12,7 @@ pub fn create_liquidity_pool(ctx: Context<CreateLiquidityPool>) -> Result<()> {
         ctx.accounts.mint_token_one.key(),
         ctx.accounts.mint_token_two.key(),
         ctx.bumps.pool,
+        ctx.accounts.payer.key(),
     ));
 
     Ok(())
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{LiquidityPool, LiquidityProvider};
+
+pub fn approve_delegate(
+    ctx: Context<ManageDelegate>,
+    delegate: Pubkey,
+    delegated_shares: u64,
+) -> Result<()> {
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+
+    liquidity_provider_account.delegate = delegate;
+    liquidity_provider_account.delegated_shares = delegated_shares;
+
+    Ok(())
+}
+
+pub fn revoke_delegate(ctx: Context<ManageDelegate>) -> Result<()> {
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+
+    liquidity_provider_account.delegate = Pubkey::default();
+    liquidity_provider_account.delegated_shares = 0_u64;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ManageDelegate<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    pub user: Signer<'info>,
+}
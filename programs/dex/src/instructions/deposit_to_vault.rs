@@ -0,0 +1,104 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+pub fn deposit_to_vault(ctx: Context<DepositToVault>, shares: u64) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let vault_liquidity_provider_account = &mut ctx.accounts.vault_liquidity_provider_account;
+
+    let now = Clock::get()?.unix_timestamp;
+
+    pool.execute_virtual_orders(&ctx.accounts.dex_configuration_account, now)?;
+
+    // Earnings that have not been harvested yet belong to the current depositors,
+    // they are part of what a vault share is worth.
+    pool.update_rewards(now)?;
+    pool.accrue_fees(vault_liquidity_provider_account)?;
+    pool.accrue_rewards(vault_liquidity_provider_account)?;
+
+    let vault_liquidity_shares =
+        CompoundingVault::liquidity_shares(pool, vault_liquidity_provider_account)?;
+
+    let vault_shares = CompoundingVault::vault_shares_for_deposit(
+        shares,
+        vault_liquidity_shares,
+        ctx.accounts.share_mint.supply,
+    )?;
+
+    if vault_shares == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    ctx.accounts.pool.transfer_shares(
+        &mut ctx.accounts.liquidity_provider_account,
+        &mut ctx.accounts.vault_liquidity_provider_account,
+        shares,
+    )?;
+
+    ctx.accounts.vault.mint_vault_shares(
+        &ctx.accounts.share_mint,
+        &ctx.accounts.user_share_token_account,
+        vault_shares,
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(vault_shares)
+}
+
+#[derive(Accounts)]
+pub struct DepositToVault<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        seeds = [CompoundingVault::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump = vault.bump,
+        has_one = share_mint,
+    )]
+    pub vault: Box<Account<'info, CompoundingVault>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(mut)]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = share_mint,
+        associated_token::authority = user,
+    )]
+    pub user_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
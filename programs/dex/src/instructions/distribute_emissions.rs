@@ -0,0 +1,104 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use std::cmp;
+
+pub fn distribute_emissions(ctx: Context<DistributeEmissions>, reward_index: u8) -> Result<()> {
+    let gauge_controller = &ctx.accounts.gauge_controller;
+    let gauge = &mut ctx.accounts.gauge;
+    let pool = &mut ctx.accounts.pool;
+
+    let now = Clock::get()?.unix_timestamp;
+    let current_epoch = gauge_controller.epoch_at(now);
+
+    if gauge.next_distribution_epoch >= current_epoch {
+        return err!(DexProgramError::EmissionsAlreadyDistributed);
+    }
+
+    // Every ended epoch that was missed is caught up on. Tallies older than the
+    // two kept by the weights have been overwritten, their emissions stay in
+    // the emission vault.
+    let first_epoch = cmp::max(
+        gauge.next_distribution_epoch,
+        current_epoch.saturating_sub(2),
+    );
+    let mut emissions = 0_u64;
+
+    for epoch in first_epoch..current_epoch {
+        let total_weight = gauge_controller.total_weights.weight_at(epoch);
+
+        if total_weight == 0 {
+            continue;
+        }
+
+        let epoch_emissions = ((gauge_controller.emissions_per_epoch as u128)
+            .checked_mul(gauge.weights.weight_at(epoch))
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            / total_weight) as u64;
+
+        emissions = emissions
+            .checked_add(epoch_emissions)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+    }
+
+    gauge.next_distribution_epoch = current_epoch;
+
+    if emissions == 0 {
+        return Ok(());
+    }
+
+    let index = pool.reward_index(reward_index, gauge_controller.emission_mint)?;
+
+    gauge_controller.transfer_from_controller(
+        &ctx.accounts.emission_vault,
+        &ctx.accounts.reward_vault_token_account,
+        emissions,
+        &ctx.accounts.token_program,
+    )?;
+
+    // The gauge's share is streamed to the pool's liquidity providers over the
+    // following epoch, on top of whatever the reward slot already emits.
+    pool.update_rewards(now)?;
+    pool.rewards[index].add_emissions(emissions, now, gauge_controller.epoch_duration)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DistributeEmissions<'info> {
+    #[account(
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        mut,
+        seeds = [Gauge::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [GaugeController::EMISSION_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub emission_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [RewardVault::SEED_PREFIX.as_bytes(), pool.key().as_ref(), gauge_controller.emission_mint.as_ref()],
+        bump,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,139 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn execute_dca_cycle(ctx: Context<ExecuteDcaCycle>) -> Result<SwapAmounts> {
+    let pool = &mut ctx.accounts.pool;
+    let position = &mut ctx.accounts.dca_position;
+    let now = Clock::get()?.unix_timestamp;
+
+    if !position.is_cycle_due(now) {
+        return err!(DexProgramError::DcaCycleNotDue);
+    }
+
+    let amount_in = position.amount_per_cycle;
+
+    let swap_amounts = position.execute_from_escrow(
+        pool,
+        &ctx.accounts.dex_configuration_account,
+        &ctx.accounts.escrow,
+        (
+            &ctx.accounts.mint_token_one,
+            &mut ctx.accounts.pool_token_account_one,
+        ),
+        &mut ctx.accounts.pool_token_account_two,
+        (
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.keeper_token_account,
+        ),
+        amount_in,
+        position.min_amount_out,
+        &ctx.accounts.token_program,
+    )?;
+
+    // Late cycles are not caught up, the schedule restarts from this execution
+    position.cycles_remaining -= 1;
+    position.next_cycle_at = now
+        .checked_add(position.cycle_interval)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    // The last cycle returns the rent to the owner right away, along with
+    // anything else that was sent to the escrow
+    if position.cycles_remaining == 0 {
+        position.refund_and_close_escrow(
+            &ctx.accounts.escrow,
+            amount_in,
+            ctx.accounts.owner_input_token_account.as_deref(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        position.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(swap_amounts)
+}
+
+#[derive(Accounts)]
+pub struct ExecuteDcaCycle<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [DcaPosition::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &dca_position.position_id.to_le_bytes()],
+        bump = dca_position.bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub dca_position: Box<Account<'info, DcaPosition>>,
+
+    #[account(
+        mut,
+        seeds = [DcaPosition::ESCROW_SEED_PREFIX.as_bytes(), dca_position.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        address = dca_position.input_mint,
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        address = dca_position.output_mint,
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Only needed for the last cycle when the escrow holds more than it sells
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub keeper_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
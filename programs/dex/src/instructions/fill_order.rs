@@ -0,0 +1,145 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn fill_order(ctx: Context<FillOrder>, amount_in: u64) -> Result<SwapAmounts> {
+    let pool = &mut ctx.accounts.pool;
+    let order = &mut ctx.accounts.order;
+
+    if order.is_expired(Clock::get()?.unix_timestamp) {
+        return err!(DexProgramError::OrderExpired);
+    }
+
+    if amount_in == 0 || amount_in > order.remaining_amount_in {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    // Limit prices are per input token, the whole fill has to reach them
+    let min_amount_out = ((amount_in as f64) * order.limit_price).ceil() as u64;
+
+    let swap_amounts = order.execute_from_escrow(
+        pool,
+        &ctx.accounts.dex_configuration_account,
+        &ctx.accounts.escrow,
+        (
+            &ctx.accounts.mint_token_one,
+            &mut ctx.accounts.pool_token_account_one,
+        ),
+        &mut ctx.accounts.pool_token_account_two,
+        (
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.keeper_token_account,
+        ),
+        amount_in,
+        min_amount_out,
+        &ctx.accounts.token_program,
+    )?;
+
+    let amount_out = swap_amounts.amount_out - keeper_fee(swap_amounts.amount_out);
+
+    order.remaining_amount_in -= amount_in;
+    order.filled_amount_out = order
+        .filled_amount_out
+        .checked_add(amount_out)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    // A fully filled order returns its rent to the owner right away, along with
+    // anything else that was sent to the escrow
+    if order.remaining_amount_in == 0 {
+        order.refund_and_close_escrow(
+            &ctx.accounts.escrow,
+            amount_in,
+            ctx.accounts.owner_input_token_account.as_deref(),
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        order.close(ctx.accounts.owner.to_account_info())?;
+    }
+
+    Ok(swap_amounts)
+}
+
+#[derive(Accounts)]
+pub struct FillOrder<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [Order::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        mut,
+        seeds = [Order::ESCROW_SEED_PREFIX.as_bytes(), order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        address = order.input_mint,
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        address = order.output_mint,
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Only needed for the final fill when the escrow holds more than the order
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub keeper_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
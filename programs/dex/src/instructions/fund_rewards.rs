@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityPoolAccount, RewardVault},
+};
+
+pub fn fund_rewards(ctx: Context<FundRewards>, reward_index: u8, amount: u64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if amount == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    let index = pool.reward_index(reward_index, ctx.accounts.reward_mint.key())?;
+
+    // Bring the accumulators up to date first so the new funds are only emitted
+    // from now on.
+    pool.update_rewards(Clock::get()?.unix_timestamp)?;
+
+    pool.transfer_token_to_pool(
+        &ctx.accounts.funder_token_account,
+        &ctx.accounts.reward_vault_token_account,
+        amount,
+        &ctx.accounts.funder,
+        &ctx.accounts.token_program,
+    )?;
+
+    pool.rewards[index].undistributed = pool.rewards[index]
+        .undistributed
+        .checked_add(amount)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [RewardVault::SEED_PREFIX.as_bytes(), pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = funder,
+    )]
+    pub funder_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub funder: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
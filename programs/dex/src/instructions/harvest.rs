@@ -0,0 +1,227 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn harvest(
+    ctx: Context<Harvest>,
+    reward_index: Option<u8>,
+    min_shares_out: u64,
+) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let vault_liquidity_provider_account = &mut ctx.accounts.vault_liquidity_provider_account;
+
+    let now = Clock::get()?.unix_timestamp;
+
+    // Long-term orders trade at the price before the harvest swap
+    pool.execute_virtual_orders(&ctx.accounts.dex_configuration_account, now)?;
+
+    pool.update_rewards(now)?;
+    pool.accrue_fees(vault_liquidity_provider_account)?;
+    pool.accrue_rewards(vault_liquidity_provider_account)?;
+
+    // Claimed fees never leave the pool vaults, they only move from the fee
+    // reserves back into the liquidity reserves below.
+    let mut amount_one = vault_liquidity_provider_account.fees_owed_one;
+    let mut amount_two = vault_liquidity_provider_account.fees_owed_two;
+
+    pool.release_fees(amount_one, amount_two)?;
+
+    vault_liquidity_provider_account.fees_owed_one = 0_u64;
+    vault_liquidity_provider_account.fees_owed_two = 0_u64;
+
+    // Rewards can only be compounded when they are paid in one of the pool tokens
+    if let Some(reward_index) = reward_index {
+        let (Some(reward_mint), Some(reward_vault_token_account)) = (
+            &ctx.accounts.reward_mint,
+            &ctx.accounts.reward_vault_token_account,
+        ) else {
+            return err!(DexProgramError::InvalidRewardVault);
+        };
+
+        let index = pool.reward_index(reward_index, reward_mint.key())?;
+
+        let (reward_vault_address, _) = Pubkey::find_program_address(
+            &[
+                RewardVault::SEED_PREFIX.as_bytes(),
+                pool.key().as_ref(),
+                reward_mint.key().as_ref(),
+            ],
+            &crate::ID,
+        );
+
+        if !reward_vault_token_account.key().eq(&reward_vault_address)
+            || (!reward_mint.key().eq(&pool.token_one) && !reward_mint.key().eq(&pool.token_two))
+        {
+            return err!(DexProgramError::InvalidRewardVault);
+        }
+
+        let rewards = vault_liquidity_provider_account.rewards_owed[index];
+        vault_liquidity_provider_account.rewards_owed[index] = 0_u64;
+        pool.release_rewards(index, rewards)?;
+
+        let pool_token_account = if reward_mint.key().eq(&ctx.accounts.mint_token_one.key()) {
+            &ctx.accounts.pool_token_account_one
+        } else {
+            &ctx.accounts.pool_token_account_two
+        };
+
+        pool.transfer_token_from_pool(
+            reward_vault_token_account,
+            pool_token_account,
+            rewards,
+            &ctx.accounts.token_program,
+        )?;
+
+        if pool.is_token_one(reward_mint.key()) {
+            amount_one = amount_one
+                .checked_add(rewards)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        } else {
+            amount_two = amount_two
+                .checked_add(rewards)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        }
+    }
+
+    if amount_one == 0 && amount_two == 0 {
+        return err!(DexProgramError::NothingToHarvest);
+    }
+
+    let (decimals_one, decimals_two) = if pool.is_token_one(ctx.accounts.mint_token_one.key()) {
+        (
+            ctx.accounts.mint_token_one.decimals,
+            ctx.accounts.mint_token_two.decimals,
+        )
+    } else {
+        (
+            ctx.accounts.mint_token_two.decimals,
+            ctx.accounts.mint_token_one.decimals,
+        )
+    };
+
+    // Swap the side held in excess through the pool so both amounts can be added
+    // back at the pool ratio.
+    let fees = pool.current_fee(&ctx.accounts.dex_configuration_account, now);
+    let (is_token_one_in, swap_amount) = pool.balancing_swap_amount(fees, amount_one, amount_two);
+
+    if swap_amount > 0 {
+        if is_token_one_in {
+            let swap_amounts = pool.apply_swap(
+                &ctx.accounts.dex_configuration_account,
+                true,
+                decimals_one,
+                swap_amount,
+                0_u16,
+            )?;
+            amount_one = amount_one
+                .checked_sub(swap_amounts.amount_in)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            amount_two = amount_two
+                .checked_add(swap_amounts.amount_out)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        } else {
+            let swap_amounts = pool.apply_swap(
+                &ctx.accounts.dex_configuration_account,
+                false,
+                decimals_two,
+                swap_amount,
+                0_u16,
+            )?;
+            amount_two = amount_two
+                .checked_sub(swap_amounts.amount_in)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            amount_one = amount_one
+                .checked_add(swap_amounts.amount_out)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        }
+    }
+
+    let shares = pool.quote_add_liquidity(amount_one, amount_two, decimals_one, decimals_two)?;
+
+    // The crank is permissionless and trades through the pool, the caller bounds
+    // what a price moved right before the harvest may cost the vault.
+    if shares < min_shares_out {
+        return err!(DexProgramError::HarvestBelowMinimum);
+    }
+
+    pool.grant_shares(vault_liquidity_provider_account, shares)?;
+
+    let new_reserves_one = pool
+        .reserve_one
+        .checked_add(amount_one)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+    let new_reserves_two = pool
+        .reserve_two
+        .checked_add(amount_two)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    pool.update_reserves(new_reserves_one, new_reserves_two)?;
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    Ok(shares)
+}
+
+#[derive(Accounts)]
+pub struct Harvest<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS | PAUSE_DEPOSITS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        seeds = [CompoundingVault::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, CompoundingVault>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    pub reward_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(mut)]
+    pub reward_vault_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    pub token_program: Program<'info, Token>,
+}
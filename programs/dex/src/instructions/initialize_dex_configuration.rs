@@ -7,11 +7,11 @@ pub fn initialize_dex_configuration(
 ) -> Result<()> {
     let dex_config = &mut ctx.accounts.dex_configuration_account;
 
-    if fees < 0_f64 || fees > 100_f64 {
+    if !(0_f64..=100_f64).contains(&fees) {
         return err!(DexProgramError::InvalidFee);
     }
 
-    dex_config.set_inner(DexConfiguration::new(fees));
+    dex_config.set_inner(DexConfiguration::new(fees, ctx.accounts.admin.key()));
 
     Ok(())
 }
@@ -0,0 +1,75 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn initialize_gauge_controller(
+    ctx: Context<InitializeGaugeController>,
+    epoch_duration: i64,
+    emissions_per_epoch: u64,
+) -> Result<()> {
+    if epoch_duration <= 0 {
+        return err!(DexProgramError::InvalidEpochDuration);
+    }
+
+    ctx.accounts
+        .gauge_controller
+        .set_inner(GaugeController::new(
+            ctx.accounts.governance_mint.key(),
+            ctx.accounts.emission_mint.key(),
+            Clock::get()?.unix_timestamp,
+            epoch_duration,
+            emissions_per_epoch,
+            ctx.bumps.gauge_controller,
+        ));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeGaugeController<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = GaugeController::ACCOUNT_SIZE,
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    pub governance_mint: Box<Account<'info, Mint>>,
+
+    pub emission_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [GaugeController::LOCKER_VAULT_SEED.as_bytes()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = gauge_controller,
+    )]
+    pub locker_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [GaugeController::EMISSION_VAULT_SEED.as_bytes()],
+        bump,
+        token::mint = emission_mint,
+        token::authority = gauge_controller,
+    )]
+    pub emission_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,89 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+pub fn lock_governance_tokens(
+    ctx: Context<LockGovernanceTokens>,
+    amount: u64,
+    unlock_time: i64,
+) -> Result<()> {
+    let vote_escrow = &mut ctx.accounts.vote_escrow;
+    let now = Clock::get()?.unix_timestamp;
+
+    if vote_escrow.owner == Pubkey::default() {
+        vote_escrow.owner = ctx.accounts.user.key();
+        vote_escrow.bump = ctx.bumps.vote_escrow;
+    }
+
+    // Locks can be topped up and extended but never shortened
+    if unlock_time <= now
+        || unlock_time < vote_escrow.lock_end
+        || unlock_time > now.saturating_add(GaugeController::MAX_LOCK_DURATION)
+    {
+        return err!(DexProgramError::InvalidLock);
+    }
+
+    if amount == 0 && vote_escrow.amount == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if amount > 0 {
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.user_token_account.to_account_info(),
+                    to: ctx.accounts.locker_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+    }
+
+    vote_escrow.amount = vote_escrow
+        .amount
+        .checked_add(amount)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+    vote_escrow.lock_end = unlock_time;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LockGovernanceTokens<'info> {
+    #[account(
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = VoteEscrow::ACCOUNT_SIZE,
+        seeds = [VoteEscrow::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump,
+    )]
+    pub vote_escrow: Box<Account<'info, VoteEscrow>>,
+
+    #[account(
+        mut,
+        seeds = [GaugeController::LOCKER_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub locker_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = gauge_controller.governance_mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityProvider},
+};
+
+pub fn lock_shares(ctx: Context<LockShares>, shares: u64, unlock_timestamp: i64) -> Result<()> {
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+    let now = Clock::get()?.unix_timestamp;
+
+    if shares == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    // Adding to a running lock may never bring its deadline forward
+    if unlock_timestamp <= now || unlock_timestamp < liquidity_provider_account.lock_until {
+        return err!(DexProgramError::InvalidLock);
+    }
+
+    let locked_shares = liquidity_provider_account
+        .locked_shares_at(now)
+        .checked_add(shares)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    if locked_shares > liquidity_provider_account.shares {
+        return err!(DexProgramError::InsufficientShares);
+    }
+
+    liquidity_provider_account.locked_shares = locked_shares;
+    liquidity_provider_account.lock_until = unlock_timestamp;
+
+    Ok(())
+}
+
+pub fn extend_lock(ctx: Context<LockShares>, unlock_timestamp: i64) -> Result<()> {
+    let liquidity_provider_account = &mut ctx.accounts.liquidity_provider_account;
+    let now = Clock::get()?.unix_timestamp;
+
+    if liquidity_provider_account.locked_shares_at(now) == 0
+        || unlock_timestamp <= liquidity_provider_account.lock_until
+    {
+        return err!(DexProgramError::InvalidLock);
+    }
+
+    liquidity_provider_account.lock_until = unlock_timestamp;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LockShares<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    pub user: Signer<'info>,
+}
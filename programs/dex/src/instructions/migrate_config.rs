@@ -0,0 +1,78 @@
+use crate::{errors::DexProgramError, helpers::realloc_account, state::*};
+use anchor_lang::prelude::*;
+
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    realloc_account::<DexConfiguration>(
+        &ctx.accounts.dex_configuration_account,
+        DexConfiguration::ACCOUNT_SIZE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    let mut dex_config = {
+        let data = ctx.accounts.dex_configuration_account.try_borrow_data()?;
+        DexConfiguration::try_deserialize(&mut &data[..])?
+    };
+
+    if dex_config.version >= DexConfiguration::VERSION {
+        return err!(DexProgramError::AccountAlreadyMigrated);
+    }
+
+    // Configurations created before the admin was recorded have no one who may
+    // claim them, only the program upgrade authority can become their admin.
+    if dex_config.admin == Pubkey::default() {
+        let (Some(new_admin), Some(program_data)) =
+            (&ctx.accounts.new_admin, &ctx.accounts.program_data)
+        else {
+            return err!(DexProgramError::Unauthorized);
+        };
+
+        let (program_data_address, _) = Pubkey::find_program_address(
+            &[crate::ID.as_ref()],
+            &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        );
+
+        if !program_data.key().eq(&program_data_address)
+            || program_data.upgrade_authority_address != Some(new_admin.key())
+        {
+            return err!(DexProgramError::Unauthorized);
+        }
+
+        dex_config.admin = new_admin.key();
+    }
+
+    if dex_config.guardian == Pubkey::default() {
+        dex_config.guardian = dex_config.admin;
+    }
+
+    dex_config.version = DexConfiguration::VERSION;
+
+    let mut data = ctx
+        .accounts
+        .dex_configuration_account
+        .try_borrow_mut_data()?;
+    dex_config.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Deserialized after it has been grown to the current layout
+    #[account(
+        mut,
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: UncheckedAccount<'info>,
+
+    // Required when the configuration has no admin yet
+    pub new_admin: Option<Signer<'info>>,
+
+    pub program_data: Option<Account<'info, ProgramData>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
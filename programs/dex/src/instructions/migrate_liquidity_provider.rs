@@ -0,0 +1,56 @@
+use crate::{errors::DexProgramError, helpers::realloc_account, state::*};
+use anchor_lang::prelude::*;
+
+pub fn migrate_liquidity_provider(ctx: Context<MigrateLiquidityProvider>) -> Result<()> {
+    realloc_account::<LiquidityProvider>(
+        &ctx.accounts.liquidity_provider_account,
+        LiquidityProvider::ACCOUNT_SIZE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    let mut liquidity_provider = {
+        let data = ctx.accounts.liquidity_provider_account.try_borrow_data()?;
+        LiquidityProvider::try_deserialize(&mut &data[..])?
+    };
+
+    if liquidity_provider.version >= LiquidityProvider::VERSION {
+        return err!(DexProgramError::AccountAlreadyMigrated);
+    }
+
+    if liquidity_provider.payer == Pubkey::default() {
+        liquidity_provider.payer = ctx.accounts.owner.key();
+    }
+
+    liquidity_provider.version = LiquidityProvider::VERSION;
+
+    let mut data = ctx
+        .accounts
+        .liquidity_provider_account
+        .try_borrow_mut_data()?;
+    liquidity_provider.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateLiquidityProvider<'info> {
+    /// CHECK: Only used to derive the liquidity provider address
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: Deserialized after it has been grown to the current layout
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: UncheckedAccount<'info>,
+
+    /// CHECK: Only used to derive the liquidity provider address
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,55 @@
+use crate::{errors::DexProgramError, helpers::realloc_account, state::*};
+use anchor_lang::prelude::*;
+
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    realloc_account::<LiquidityPool>(
+        &ctx.accounts.pool,
+        LiquidityPool::ACCOUNT_SIZE,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    let mut pool = {
+        let data = ctx.accounts.pool.try_borrow_data()?;
+        LiquidityPool::try_deserialize(&mut &data[..])?
+    };
+
+    if pool.version >= LiquidityPool::VERSION {
+        return err!(DexProgramError::AccountAlreadyMigrated);
+    }
+
+    // The admin would inherit the rent refund below, it has to be set first
+    if ctx.accounts.dex_configuration_account.admin == Pubkey::default() {
+        return err!(DexProgramError::ConfigurationNotMigrated);
+    }
+
+    // Pools created before the payer was recorded refund their rent to the admin
+    if pool.payer == Pubkey::default() {
+        pool.payer = ctx.accounts.dex_configuration_account.admin;
+    }
+
+    pool.version = LiquidityPool::VERSION;
+
+    let mut data = ctx.accounts.pool.try_borrow_mut_data()?;
+    pool.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    /// CHECK: Deserialized after it has been grown to the current layout
+    #[account(mut)]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
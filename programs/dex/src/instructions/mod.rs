@@ -1,11 +1,109 @@
 pub mod add_liquidity;
+pub mod cancel_order;
+pub mod cancel_trigger_order;
+pub mod claim_fees;
+pub mod claim_rewards;
+pub mod close_dca_position;
+pub mod close_liquidity_provider;
+pub mod close_pool;
+pub mod close_reward_vault;
+pub mod configure_circuit_breaker;
+pub mod configure_dynamic_fee;
+pub mod configure_fee_discounts;
+pub mod configure_referral_fee;
+pub mod configure_trade_limits;
+pub mod create_compounding_vault;
+pub mod create_gauge;
 pub mod create_liquidity_pool;
+pub mod delegate;
+pub mod deposit_to_vault;
+pub mod distribute_emissions;
+pub mod execute_dca_cycle;
+pub mod fill_order;
+pub mod fund_rewards;
+pub mod harvest;
 pub mod initialize_dex_configuration;
+pub mod initialize_gauge_controller;
+pub mod lock_governance_tokens;
+pub mod lock_shares;
+pub mod migrate_config;
+pub mod migrate_liquidity_provider;
+pub mod migrate_pool;
+pub mod open_dca_position;
+pub mod pause_dex;
+pub mod pause_pool;
+pub mod place_long_term_order;
+pub mod place_order;
+pub mod place_trigger_order;
+pub mod quote;
+pub mod register_referrer;
+pub mod register_rfq_maker;
 pub mod remove_liquidity;
+pub mod rfq_swap;
+pub mod set_emission_rate;
+pub mod set_guardian;
+pub mod skim;
+pub mod stake;
 pub mod swap;
+pub mod sync;
+pub mod transfer_shares;
+pub mod trigger_order;
+pub mod vote_gauge;
+pub mod withdraw_from_vault;
+pub mod withdraw_governance_tokens;
+pub mod withdraw_long_term_order;
 
 pub use add_liquidity::*;
+pub use cancel_order::*;
+pub use cancel_trigger_order::*;
+pub use claim_fees::*;
+pub use claim_rewards::*;
+pub use close_dca_position::*;
+pub use close_liquidity_provider::*;
+pub use close_pool::*;
+pub use close_reward_vault::*;
+pub use configure_circuit_breaker::*;
+pub use configure_dynamic_fee::*;
+pub use configure_fee_discounts::*;
+pub use configure_referral_fee::*;
+pub use configure_trade_limits::*;
+pub use create_compounding_vault::*;
+pub use create_gauge::*;
 pub use create_liquidity_pool::*;
+pub use delegate::*;
+pub use deposit_to_vault::*;
+pub use distribute_emissions::*;
+pub use execute_dca_cycle::*;
+pub use fill_order::*;
+pub use fund_rewards::*;
+pub use harvest::*;
 pub use initialize_dex_configuration::*;
+pub use initialize_gauge_controller::*;
+pub use lock_governance_tokens::*;
+pub use lock_shares::*;
+pub use migrate_config::*;
+pub use migrate_liquidity_provider::*;
+pub use migrate_pool::*;
+pub use open_dca_position::*;
+pub use pause_dex::*;
+pub use pause_pool::*;
+pub use place_long_term_order::*;
+pub use place_order::*;
+pub use place_trigger_order::*;
+pub use quote::*;
+pub use register_referrer::*;
+pub use register_rfq_maker::*;
 pub use remove_liquidity::*;
+pub use rfq_swap::*;
+pub use set_emission_rate::*;
+pub use set_guardian::*;
+pub use skim::*;
+pub use stake::*;
 pub use swap::*;
+pub use sync::*;
+pub use transfer_shares::*;
+pub use trigger_order::*;
+pub use vote_gauge::*;
+pub use withdraw_from_vault::*;
+pub use withdraw_governance_tokens::*;
+pub use withdraw_long_term_order::*;
@@ -0,0 +1,105 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn open_dca_position(
+    ctx: Context<OpenDcaPosition>,
+    position_id: u64,
+    amount_per_cycle: u64,
+    cycle_interval: i64,
+    cycles: u64,
+    min_amount_out: u64,
+) -> Result<()> {
+    if amount_per_cycle == 0 || cycles == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if cycle_interval <= 0 {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    let amount = amount_per_cycle
+        .checked_mul(cycles)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    // The first cycle can be executed right away
+    ctx.accounts.dca_position.set_inner(DcaPosition {
+        owner: ctx.accounts.owner.key(),
+        pool: ctx.accounts.pool.key(),
+        position_id,
+        input_mint: ctx.accounts.mint_token_one.key(),
+        output_mint: ctx.accounts.mint_token_two.key(),
+        amount_per_cycle,
+        cycle_interval,
+        cycles_remaining: cycles,
+        min_amount_out,
+        next_cycle_at: Clock::get()?.unix_timestamp,
+        bump: ctx.bumps.dca_position,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct OpenDcaPosition<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = DcaPosition::ACCOUNT_SIZE,
+        seeds = [DcaPosition::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+    )]
+    pub dca_position: Box<Account<'info, DcaPosition>>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [DcaPosition::ESCROW_SEED_PREFIX.as_bytes(), dca_position.key().as_ref()],
+        bump,
+        token::mint = mint_token_one,
+        token::authority = dca_position,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,46 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn pause_dex(ctx: Context<PauseDex>, flags: u8) -> Result<()> {
+    let dex_config = &mut ctx.accounts.dex_configuration_account;
+
+    if flags == 0 || flags & !PAUSE_ALL != 0 {
+        return err!(DexProgramError::InvalidPauseFlags);
+    }
+
+    if !dex_config.can_pause(ctx.accounts.authority.key(), flags) {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    dex_config.paused |= flags;
+
+    Ok(())
+}
+
+pub fn unpause_dex(ctx: Context<PauseDex>, flags: u8) -> Result<()> {
+    let dex_config = &mut ctx.accounts.dex_configuration_account;
+
+    if flags == 0 || flags & !PAUSE_ALL != 0 {
+        return err!(DexProgramError::InvalidPauseFlags);
+    }
+
+    if !ctx.accounts.authority.key().eq(&dex_config.admin) {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    dex_config.paused &= !flags;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PauseDex<'info> {
+    #[account(
+        mut,
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,61 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn pause_pool(ctx: Context<PausePool>, flags: u8) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if flags == 0 || flags & !PAUSE_ALL != 0 {
+        return err!(DexProgramError::InvalidPauseFlags);
+    }
+
+    if !ctx
+        .accounts
+        .dex_configuration_account
+        .can_pause(ctx.accounts.authority.key(), flags)
+    {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    pool.paused |= flags;
+
+    Ok(())
+}
+
+pub fn unpause_pool(ctx: Context<PausePool>, flags: u8) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if flags == 0 || flags & !PAUSE_ALL != 0 {
+        return err!(DexProgramError::InvalidPauseFlags);
+    }
+
+    if !ctx
+        .accounts
+        .authority
+        .key()
+        .eq(&ctx.accounts.dex_configuration_account.admin)
+    {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    pool.paused &= !flags;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PausePool<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub authority: Signer<'info>,
+}
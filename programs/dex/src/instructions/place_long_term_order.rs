@@ -0,0 +1,132 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn place_long_term_order(
+    ctx: Context<PlaceLongTermOrder>,
+    order_id: u64,
+    amount_in: u64,
+    intervals: u64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if intervals == 0 || intervals > Twamm::MAX_ORDER_INTERVALS {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    let sell_rate = amount_in / intervals;
+
+    if sell_rate == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    // Bring the virtual order pools up to date so the order only sells from the
+    // next interval boundary onwards.
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    // Any remainder that does not divide evenly stays with the owner
+    let amount_in = sell_rate * intervals;
+    let side = if pool.is_token_one(ctx.accounts.mint_token_one.key()) {
+        0
+    } else {
+        1
+    };
+    let expiry_interval = pool.twamm.last_executed_interval + intervals;
+
+    pool.transfer_token_to_pool(
+        &ctx.accounts.owner_token_account,
+        &ctx.accounts.pool_token_account_one,
+        amount_in,
+        &ctx.accounts.owner,
+        &ctx.accounts.token_program,
+    )?;
+
+    pool.twamm.credit(side, amount_in)?;
+    pool.twamm.add_order(side, sell_rate, expiry_interval)?;
+
+    ctx.accounts.long_term_order.set_inner(LongTermOrder {
+        owner: ctx.accounts.owner.key(),
+        pool: pool.key(),
+        order_id,
+        input_mint: ctx.accounts.mint_token_one.key(),
+        output_mint: ctx.accounts.mint_token_two.key(),
+        sell_rate,
+        expiry_interval,
+        earnings_checkpoint: pool.twamm.order_pools[side].earnings_per_sell_rate,
+        bump: ctx.bumps.long_term_order,
+    });
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceLongTermOrder<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LongTermOrder::ACCOUNT_SIZE,
+        seeds = [LongTermOrder::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &order_id.to_le_bytes()],
+        bump,
+    )]
+    pub long_term_order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,103 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn place_order(
+    ctx: Context<PlaceOrder>,
+    order_id: u64,
+    amount_in: u64,
+    limit_price: f64,
+    expiry: i64,
+) -> Result<()> {
+    if amount_in == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if !limit_price.is_finite() || limit_price <= 0_f64 {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    if expiry <= Clock::get()?.unix_timestamp {
+        return err!(DexProgramError::OrderExpired);
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    ctx.accounts.order.set_inner(Order {
+        owner: ctx.accounts.owner.key(),
+        pool: ctx.accounts.pool.key(),
+        order_id,
+        input_mint: ctx.accounts.mint_token_one.key(),
+        output_mint: ctx.accounts.mint_token_two.key(),
+        amount_in,
+        remaining_amount_in: amount_in,
+        filled_amount_out: 0_u64,
+        limit_price,
+        expiry,
+        bump: ctx.bumps.order,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOrder<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = Order::ACCOUNT_SIZE,
+        seeds = [Order::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &order_id.to_le_bytes()],
+        bump,
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [Order::ESCROW_SEED_PREFIX.as_bytes(), order.key().as_ref()],
+        bump,
+        token::mint = mint_token_one,
+        token::authority = order,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,99 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn place_trigger_order(
+    ctx: Context<PlaceTriggerOrder>,
+    order_id: u64,
+    amount_in: u64,
+    trigger_price: f64,
+    is_take_profit: bool,
+    min_amount_out: u64,
+) -> Result<()> {
+    if amount_in == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if !trigger_price.is_finite() || trigger_price <= 0_f64 {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.owner_token_account.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    ctx.accounts.trigger_order.set_inner(TriggerOrder {
+        owner: ctx.accounts.owner.key(),
+        pool: ctx.accounts.pool.key(),
+        order_id,
+        input_mint: ctx.accounts.mint_token_one.key(),
+        output_mint: ctx.accounts.mint_token_two.key(),
+        amount_in,
+        trigger_price,
+        is_take_profit,
+        min_amount_out,
+        bump: ctx.bumps.trigger_order,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceTriggerOrder<'info> {
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = TriggerOrder::ACCOUNT_SIZE,
+        seeds = [TriggerOrder::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &order_id.to_le_bytes()],
+        bump,
+    )]
+    pub trigger_order: Box<Account<'info, TriggerOrder>>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [TriggerOrder::ESCROW_SEED_PREFIX.as_bytes(), trigger_order.key().as_ref()],
+        bump,
+        token::mint = mint_token_one,
+        token::authority = trigger_order,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+        token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,205 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::Mint;
+
+use crate::{
+    errors::DexProgramError,
+    state::{DexConfiguration, LiquidityAmounts, LiquidityPool, StakeAccount, SwapAmounts},
+};
+
+pub fn quote_swap_exact_in(
+    ctx: Context<Quote>,
+    amount_in: u64,
+    referral_fee_bps: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool = settled_pool(&ctx, now)?;
+    let mint_in = &ctx.accounts.mint_token_one;
+    let dex_configuration = &ctx.accounts.dex_configuration_account;
+
+    let fees = quote_fee(&ctx, &pool, now);
+    let is_token_one_in = pool.is_token_one(mint_in.key());
+
+    // Same split as the swap: the referrer's cut never reaches the pool
+    let referral_fee = dex_configuration.referral_fee(amount_in, referral_fee_bps)?;
+    let swap_amount = amount_in
+        .checked_sub(referral_fee)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    let mut swap_amounts =
+        pool.quote_swap_exact_in(fees, is_token_one_in, mint_in.decimals, swap_amount)?;
+
+    pool.check_trade_limits(is_token_one_in, &swap_amounts)?;
+    check_circuit_breaker(&mut pool, is_token_one_in, &swap_amounts)?;
+
+    swap_amounts.amount_in = amount_in;
+    swap_amounts.referral_fee = referral_fee;
+
+    set_return_data(&swap_amounts.try_to_vec()?);
+
+    Ok(())
+}
+
+pub fn quote_swap_exact_out(
+    ctx: Context<Quote>,
+    amount_out: u64,
+    referral_fee_bps: u16,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let mut pool = settled_pool(&ctx, now)?;
+    let mint_in = &ctx.accounts.mint_token_one;
+    let dex_configuration = &ctx.accounts.dex_configuration_account;
+
+    let fees = quote_fee(&ctx, &pool, now);
+    let is_token_one_in = pool.is_token_one(mint_in.key());
+
+    let mut swap_amounts =
+        pool.quote_swap_exact_out(fees, is_token_one_in, mint_in.decimals, amount_out)?;
+
+    pool.check_trade_limits(is_token_one_in, &swap_amounts)?;
+    check_circuit_breaker(&mut pool, is_token_one_in, &swap_amounts)?;
+
+    // Gross the input up so that what is left after the referral fee still buys
+    // `amount_out`, rounding up so the swap never falls short.
+    if referral_fee_bps >= 10_000 {
+        return err!(DexProgramError::ReferralFeeTooHigh);
+    }
+
+    let kept_bps = 10_000_u128 - referral_fee_bps as u128;
+    let amount_in = (swap_amounts.amount_in as u128)
+        .checked_mul(10_000_u128)
+        .and_then(|value| value.checked_add(kept_bps - 1))
+        .map(|value| value / kept_bps)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+    let amount_in =
+        u64::try_from(amount_in).map_err(|_| DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    swap_amounts.referral_fee = dex_configuration.referral_fee(amount_in, referral_fee_bps)?;
+    swap_amounts.amount_in = amount_in;
+
+    set_return_data(&swap_amounts.try_to_vec()?);
+
+    Ok(())
+}
+
+// The pool as the next transaction will find it. Long-term orders due by now are
+// executed on a copy, quotes never write to the pool.
+fn settled_pool(ctx: &Context<Quote>, now: i64) -> Result<LiquidityPool> {
+    let mut pool = LiquidityPool::clone(&ctx.accounts.pool);
+    pool.execute_virtual_orders(&ctx.accounts.dex_configuration_account, now)?;
+
+    Ok(pool)
+}
+
+// The swap would trip the pool's circuit breaker if it moved the price further
+// than the breaker allows in the current slot or window.
+fn check_circuit_breaker(
+    pool: &mut LiquidityPool,
+    is_token_one_in: bool,
+    swap_amounts: &SwapAmounts,
+) -> Result<()> {
+    let (reserve_one, reserve_two) = pool.reserves_after_swap(is_token_one_in, swap_amounts)?;
+
+    let price_before = LiquidityPool::spot_price(pool.reserve_one, pool.reserve_two);
+    let price_after = LiquidityPool::spot_price(reserve_one, reserve_two);
+
+    pool.check_circuit_breaker(price_before, price_after, Clock::get()?.slot)
+}
+
+// Fee the swap would charge, including the staking discount of the trader whose
+// stake account is passed in.
+fn quote_fee(ctx: &Context<Quote>, pool: &LiquidityPool, now: i64) -> f64 {
+    let dex_configuration = &ctx.accounts.dex_configuration_account;
+
+    let fee_discount_bps = match ctx.accounts.stake_account.as_ref() {
+        Some(stake_account) => dex_configuration.fee_discount_bps(stake_account.active_stake(now)),
+        None => 0_u16,
+    };
+
+    pool.discounted_fee(dex_configuration, now, fee_discount_bps)
+}
+
+pub fn quote_add_liquidity(ctx: Context<Quote>, amount_one: u64, amount_two: u64) -> Result<()> {
+    let pool = settled_pool(&ctx, Clock::get()?.unix_timestamp)?;
+    let mint_token_one = &ctx.accounts.mint_token_one;
+    let mint_token_two = &ctx.accounts.mint_token_two;
+
+    let shares = if pool.is_token_one(mint_token_one.key()) {
+        pool.quote_add_liquidity(
+            amount_one,
+            amount_two,
+            mint_token_one.decimals,
+            mint_token_two.decimals,
+        )?
+    } else {
+        pool.quote_add_liquidity(
+            amount_two,
+            amount_one,
+            mint_token_two.decimals,
+            mint_token_one.decimals,
+        )?
+    };
+
+    let liquidity_amounts = LiquidityAmounts {
+        shares,
+        amount_one,
+        amount_two,
+    };
+
+    set_return_data(&liquidity_amounts.try_to_vec()?);
+
+    Ok(())
+}
+
+pub fn quote_remove_liquidity(ctx: Context<Quote>, shares: u64) -> Result<()> {
+    let pool = settled_pool(&ctx, Clock::get()?.unix_timestamp)?;
+
+    let (pool_amount_one, pool_amount_two) = pool.quote_remove_liquidity(shares)?;
+
+    let (amount_one, amount_two) = if pool.is_token_one(ctx.accounts.mint_token_one.key()) {
+        (pool_amount_one, pool_amount_two)
+    } else {
+        (pool_amount_two, pool_amount_one)
+    };
+
+    let liquidity_amounts = LiquidityAmounts {
+        shares,
+        amount_one,
+        amount_two,
+    };
+
+    set_return_data(&liquidity_amounts.try_to_vec()?);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        seeds = [StakeAccount::SEED_PREFIX.as_bytes(), stake_account.owner.as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Option<Box<Account<'info, StakeAccount>>>,
+}
@@ -0,0 +1,32 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+    ctx.accounts.referrer_stats.set_inner(ReferrerStats::new(
+        ctx.accounts.referrer.key(),
+        ctx.accounts.mint.key(),
+        ctx.bumps.referrer_stats,
+    ));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        init,
+        payer = referrer,
+        space = ReferrerStats::ACCOUNT_SIZE,
+        seeds = [ReferrerStats::SEED_PREFIX.as_bytes(), referrer.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub referrer_stats: Box<Account<'info, ReferrerStats>>,
+
+    pub mint: Box<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
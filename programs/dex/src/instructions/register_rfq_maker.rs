@@ -0,0 +1,33 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+// Creates the nonce bitmap page a maker's quotes are tracked in, one page covers
+// `MakerNonces::NONCES_PER_PAGE` consecutive nonces.
+pub fn register_rfq_maker(ctx: Context<RegisterRfqMaker>, page: u64) -> Result<()> {
+    ctx.accounts.maker_nonces.set_inner(MakerNonces {
+        maker: ctx.accounts.maker.key(),
+        page,
+        bitmap: [0_u64; MakerNonces::BITMAP_WORDS],
+        bump: ctx.bumps.maker_nonces,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(page: u64)]
+pub struct RegisterRfqMaker<'info> {
+    #[account(
+        init,
+        payer = maker,
+        space = MakerNonces::ACCOUNT_SIZE,
+        seeds = [MakerNonces::SEED_PREFIX.as_bytes(), maker.key().as_ref(), &page.to_le_bytes()],
+        bump,
+    )]
+    pub maker_nonces: Box<Account<'info, MakerNonces>>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
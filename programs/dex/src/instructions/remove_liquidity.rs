@@ -1,47 +1,127 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::{spl_token::native_mint, Mint, Token, TokenAccount},
 };
 
 use crate::{
     errors::DexProgramError,
-    state::{LiquidityPool, LiquidityPoolAccount, LiquidityProvider},
+    state::{
+        native_sol_legs, settlement_account, DexConfiguration, LiquidityAmounts, LiquidityPool,
+        LiquidityPoolAccount, LiquidityProvider, PAUSE_WITHDRAWALS,
+    },
 };
 
-pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, shares: u64) -> Result<()> {
+pub fn remove_liquidity(
+    ctx: Context<RemoveLiquidity>,
+    shares: u64,
+    native_sol: bool,
+) -> Result<LiquidityAmounts> {
     let pool = &mut ctx.accounts.pool;
 
+    // Long-term orders trade at the price before this change
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    // Proceeds always go to the owner's token accounts, a delegate can only
+    // unwind the position up to its allowance.
+    if !ctx.accounts.user.key().eq(&ctx.accounts.owner.key()) {
+        ctx.accounts
+            .liquidity_provider_account
+            .spend_delegated_shares(shares)?;
+    }
+
+    // A native SOL leg settles through the temporary wrapped SOL account, the
+    // user needs no token account for it
+    let (native_sol_account_one, native_sol_account_two) = native_sol_legs(
+        native_sol,
+        ctx.accounts.native_sol_account.as_deref_mut(),
+        &ctx.accounts.mint_token_one,
+    )?;
+
     let token_one_accounts = (
         &mut *ctx.accounts.mint_token_one,
         &mut *ctx.accounts.pool_token_account_one,
-        &mut *ctx.accounts.user_token_account_one,
+        settlement_account(
+            native_sol_account_one,
+            ctx.accounts.user_token_account_one.as_deref_mut(),
+        )?,
     );
 
     let token_two_accounts = (
         &mut *ctx.accounts.mint_token_two,
         &mut *ctx.accounts.pool_token_account_two,
-        &mut *ctx.accounts.user_token_account_two,
+        settlement_account(
+            native_sol_account_two,
+            ctx.accounts.user_token_account_two.as_deref_mut(),
+        )?,
     );
 
-    pool.remove_liquidity(
+    let liquidity_amounts = pool.remove_liquidity(
         token_one_accounts,
         token_two_accounts,
         shares,
-        &mut *ctx.accounts.liquidity_provider_account,
-        &ctx.accounts.user,
+        native_sol,
+        &mut ctx.accounts.liquidity_provider_account,
         &ctx.accounts.token_program,
     )?;
 
-    Ok(())
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    // Closing the temporary wrapped SOL account unwraps the proceeds and returns
+    // the rent to the signer, a delegate forwards the proceeds to the owner.
+    if let Some(native_sol_account) = ctx.accounts.native_sol_account.as_ref() {
+        pool.close_pool_token_account(
+            native_sol_account,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+
+        if native_sol && !ctx.accounts.user.key().eq(&ctx.accounts.owner.key()) {
+            let proceeds = if native_sol_account
+                .mint
+                .eq(&ctx.accounts.mint_token_one.key())
+            {
+                liquidity_amounts.amount_one
+            } else {
+                liquidity_amounts.amount_two
+            };
+
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                proceeds,
+            )?;
+        }
+    }
+
+    Ok(liquidity_amounts)
 }
 
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
     #[account(
         mut,
         seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_WITHDRAWALS) @ DexProgramError::OperationPaused
     )]
     pub pool: Box<Account<'info, LiquidityPool>>,
 
@@ -49,8 +129,9 @@ pub struct RemoveLiquidity<'info> {
         init_if_needed,
         payer = user,
         space = LiquidityProvider::ACCOUNT_SIZE,
-        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref()],
         bump,
+        constraint = liquidity_provider_account.is_owner_or_delegate(owner.key(), user.key()) @ DexProgramError::Unauthorized,
     )]
     pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
 
@@ -79,18 +160,39 @@ pub struct RemoveLiquidity<'info> {
     pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_token_one,
-        associated_token::authority = user,
+        associated_token::authority = owner,
     )]
-    pub user_token_account_one: Box<Account<'info, TokenAccount>>,
+    pub user_token_account_one: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_token_two,
-        associated_token::authority = user,
+        associated_token::authority = owner,
+    )]
+    pub user_token_account_two: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Temporary wrapped SOL account a native SOL leg settles through. It is owned
+    // by the pool and closed again before the instruction ends.
+    #[account(
+        init,
+        payer = user,
+        seeds = [LiquidityPool::NATIVE_SOL_SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool,
     )]
-    pub user_token_account_two: Box<Account<'info, TokenAccount>>,
+    pub native_sol_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Box<Account<'info, Mint>>>,
+
+    /// CHECK: Owner of the position, the signer must be the owner or its delegate
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub user: Signer<'info>,
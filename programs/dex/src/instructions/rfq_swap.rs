@@ -0,0 +1,230 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program_option::COption, sysvar};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn rfq_swap(
+    ctx: Context<RfqSwap>,
+    amount_in: u64,
+    min_amount_out: u64,
+    quote: Option<RfqQuote>,
+) -> Result<RfqSwapAmounts> {
+    if amount_in == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if let Some(quote) = quote {
+        if let Some(swap_amounts) = fill_quote(
+            ctx.accounts,
+            &quote,
+            amount_in,
+            min_amount_out,
+            ctx.bumps.rfq_authority,
+        )? {
+            return Ok(RfqSwapAmounts {
+                swap_amounts,
+                filled_by_quote: true,
+            });
+        }
+    }
+
+    // Without a usable quote the trade goes through the pool instead
+    let pool = &mut ctx.accounts.pool;
+
+    pool.transfer_token_to_pool(
+        &ctx.accounts.taker_token_account_one,
+        &ctx.accounts.pool_token_account_one,
+        amount_in,
+        &ctx.accounts.taker,
+        &ctx.accounts.token_program,
+    )?;
+
+    let swap_amounts = pool.settle_swap(
+        &ctx.accounts.dex_configuration_account,
+        (
+            &ctx.accounts.mint_token_one,
+            &mut ctx.accounts.pool_token_account_one,
+        ),
+        &mut ctx.accounts.pool_token_account_two,
+        amount_in,
+        min_amount_out,
+        &ctx.accounts.taker_token_account_two,
+        None,
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(RfqSwapAmounts {
+        swap_amounts,
+        filled_by_quote: false,
+    })
+}
+
+// Settles the quote directly between maker and taker. A quote that is malformed
+// or not signed by the maker fails the swap, one that can no longer be filled
+// returns `None` so the caller falls back to the pool.
+fn fill_quote(
+    accounts: &mut RfqSwap,
+    quote: &RfqQuote,
+    amount_in: u64,
+    min_amount_out: u64,
+    authority_bump: u8,
+) -> Result<Option<SwapAmounts>> {
+    let (Some(maker_token_account_one), Some(maker_token_account_two), Some(maker_nonces)) = (
+        accounts.maker_token_account_one.as_ref(),
+        accounts.maker_token_account_two.as_ref(),
+        accounts.maker_nonces.as_mut(),
+    ) else {
+        return Ok(None);
+    };
+
+    if !quote.input_mint.eq(&accounts.mint_token_one.key())
+        || !quote.output_mint.eq(&accounts.mint_token_two.key())
+        || quote.amount_in != amount_in
+        || !(quote.taker.eq(&Pubkey::default()) || quote.taker.eq(&accounts.taker.key()))
+        || !maker_token_account_one.owner.eq(&quote.maker)
+        || !maker_token_account_two.owner.eq(&quote.maker)
+        || !maker_nonces.maker.eq(&quote.maker)
+        || maker_nonces.page != MakerNonces::page_of(quote.nonce)
+    {
+        return err!(DexProgramError::InvalidRfqQuote);
+    }
+
+    quote.verify_signature(&accounts.instructions_sysvar)?;
+
+    let can_settle = maker_token_account_two.delegate
+        == COption::Some(accounts.rfq_authority.key())
+        && maker_token_account_two.delegated_amount >= quote.amount_out
+        && maker_token_account_two.amount >= quote.amount_out;
+
+    if quote.is_expired(Clock::get()?.unix_timestamp)
+        || maker_nonces.is_used(quote.nonce)
+        || quote.amount_out < min_amount_out
+        || !can_settle
+    {
+        return Ok(None);
+    }
+
+    maker_nonces.mark_used(quote.nonce);
+
+    token::transfer(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: accounts.taker_token_account_one.to_account_info(),
+                to: maker_token_account_one.to_account_info(),
+                authority: accounts.taker.to_account_info(),
+            },
+        ),
+        amount_in,
+    )?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: maker_token_account_two.to_account_info(),
+                to: accounts.taker_token_account_two.to_account_info(),
+                authority: accounts.rfq_authority.to_account_info(),
+            },
+            &[&[RfqQuote::AUTHORITY_SEED.as_bytes(), &[authority_bump]]],
+        ),
+        quote.amount_out,
+    )?;
+
+    Ok(Some(SwapAmounts {
+        amount_in,
+        amount_out: quote.amount_out,
+        fee_amount: 0_u64,
+        referral_fee: 0_u64,
+    }))
+}
+
+#[derive(Accounts)]
+pub struct RfqSwap<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+        token::authority = taker,
+    )]
+    pub taker_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+        token::authority = taker,
+    )]
+    pub taker_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+    )]
+    pub maker_token_account_one: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub maker_token_account_two: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [MakerNonces::SEED_PREFIX.as_bytes(), maker_nonces.maker.as_ref(), &maker_nonces.page.to_le_bytes()],
+        bump = maker_nonces.bump,
+    )]
+    pub maker_nonces: Option<Box<Account<'info, MakerNonces>>>,
+
+    /// CHECK: Delegate makers approve on the token accounts they settle from
+    #[account(
+        seeds = [RfqQuote::AUTHORITY_SEED.as_bytes()],
+        bump,
+    )]
+    pub rfq_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the maker's Ed25519 signature
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    errors::DexProgramError,
+    state::{CompoundingVault, DexConfiguration, LiquidityPool, RewardVault},
+};
+
+pub fn set_emission_rate(
+    ctx: Context<SetEmissionRate>,
+    reward_index: u8,
+    emission_rate: u64,
+    end_time: i64,
+) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    let reward_mint = ctx.accounts.reward_mint.key();
+    let now = Clock::get()?.unix_timestamp;
+    let index = reward_index as usize;
+
+    if index >= LiquidityPool::MAX_REWARDS || end_time < now {
+        return err!(DexProgramError::InvalidPoolParameter);
+    }
+
+    // Settle what was emitted under the previous rate before changing it
+    pool.update_rewards(now)?;
+
+    let reward_vault = &pool.rewards[index];
+
+    if !reward_vault.is_initialized() {
+        if pool
+            .rewards
+            .iter()
+            .any(|reward_vault| reward_vault.mint == reward_mint)
+        {
+            return err!(DexProgramError::InvalidRewardVault);
+        }
+
+        if !pool.token_one.eq(&reward_mint)
+            && !pool.token_two.eq(&reward_mint)
+            && !ctx.accounts.compounding_vault.data_is_empty()
+        {
+            return err!(DexProgramError::RewardNotCompoundable);
+        }
+
+        pool.rewards[index] = RewardVault {
+            mint: reward_mint,
+            last_update: now,
+            ..RewardVault::default()
+        };
+    } else if reward_vault.mint != reward_mint {
+        return err!(DexProgramError::InvalidRewardVault);
+    }
+
+    pool.rewards[index].emission_rate = emission_rate;
+    pool.rewards[index].end_time = end_time;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetEmissionRate<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+        has_one = admin @ DexProgramError::Unauthorized,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    pub reward_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [RewardVault::SEED_PREFIX.as_bytes(), pool.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = pool,
+    )]
+    pub reward_vault_token_account: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Only checked for existence, a vault can't compound rewards paid in other tokens
+    #[account(
+        seeds = [CompoundingVault::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump,
+    )]
+    pub compounding_vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    errors::DexProgramError,
+    state::{DexConfiguration, LiquidityPool, LiquidityPoolAccount},
+};
+
+pub fn skim(ctx: Context<SkimReserves>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Long-term orders have to settle first so their balances are current
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let (held_one, held_two) = pool.committed_balances()?;
+
+    let (reserve_one, reserve_two) = if pool.is_token_one(ctx.accounts.mint_token_one.key()) {
+        (held_one, held_two)
+    } else {
+        (held_two, held_one)
+    };
+
+    // Anything held by the vaults beyond what the pool has committed was transferred
+    // in directly and does not belong to the liquidity providers.
+    let excess_one = ctx
+        .accounts
+        .pool_token_account_one
+        .amount
+        .saturating_sub(reserve_one);
+    let excess_two = ctx
+        .accounts
+        .pool_token_account_two
+        .amount
+        .saturating_sub(reserve_two);
+
+    if excess_one > 0 {
+        pool.transfer_token_from_pool(
+            &ctx.accounts.pool_token_account_one,
+            &ctx.accounts.recipient_token_account_one,
+            excess_one,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    if excess_two > 0 {
+        pool.transfer_token_from_pool(
+            &ctx.accounts.pool_token_account_two,
+            &ctx.accounts.recipient_token_account_two,
+            excess_two,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SkimReserves<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+    )]
+    pub recipient_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub recipient_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
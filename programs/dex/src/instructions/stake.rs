@@ -0,0 +1,144 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+    let stake_account = &mut ctx.accounts.stake_account;
+
+    if amount == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if stake_account.owner == Pubkey::default() {
+        stake_account.owner = ctx.accounts.user.key();
+        stake_account.bump = ctx.bumps.stake_account;
+    }
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    stake_account.add_stake(amount, Clock::get()?.unix_timestamp)?;
+
+    Ok(())
+}
+
+pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+    ctx.accounts
+        .stake_account
+        .remove_stake(amount, Clock::get()?.unix_timestamp)?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.stake_vault_authority.to_account_info(),
+            },
+            &[&[
+                StakeAccount::STAKE_VAULT_AUTHORITY_SEED.as_bytes(),
+                &[ctx.bumps.stake_vault_authority],
+            ]],
+        ),
+        amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = StakeAccount::ACCOUNT_SIZE,
+        seeds = [StakeAccount::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        seeds = [StakeAccount::STAKE_VAULT_SEED.as_bytes()],
+        bump,
+        token::mint = governance_mint,
+        token::authority = stake_vault_authority,
+    )]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Signs for the stake vault, holds no data
+    #[account(
+        seeds = [StakeAccount::STAKE_VAULT_AUTHORITY_SEED.as_bytes()],
+        bump,
+    )]
+    pub stake_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        address = gauge_controller.governance_mint,
+    )]
+    pub governance_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = governance_mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        seeds = [StakeAccount::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Box<Account<'info, StakeAccount>>,
+
+    #[account(
+        mut,
+        seeds = [StakeAccount::STAKE_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub stake_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: Signs for the stake vault, holds no data
+    #[account(
+        seeds = [StakeAccount::STAKE_VAULT_AUTHORITY_SEED.as_bytes()],
+        bump,
+    )]
+    pub stake_vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = stake_vault.mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
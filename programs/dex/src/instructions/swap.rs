@@ -1,39 +1,160 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token::{spl_token::native_mint, Mint, Token, TokenAccount},
 };
 
 use crate::{
     errors::DexProgramError,
-    state::{DexConfiguration, LiquidityPool, LiquidityPoolAccount},
+    helpers::{transfer_from_user, wrap_sol_from_user},
+    state::{
+        native_sol_legs, settlement_account, DexConfiguration, LiquidityPool, LiquidityPoolAccount,
+        ReferrerStats, StakeAccount, SwapAmounts, PAUSE_SWAPS,
+    },
 };
 
-pub fn swap(ctx: Context<Swap>, amount: u64) -> Result<()> {
+pub fn swap(
+    ctx: Context<Swap>,
+    amount: u64,
+    native_sol: bool,
+    deadline: Option<i64>,
+    referral_fee_bps: u16,
+) -> Result<SwapAmounts> {
+    if let Some(deadline) = deadline {
+        if Clock::get()?.unix_timestamp > deadline {
+            return err!(DexProgramError::DeadlineExceeded);
+        }
+    }
+
     let pool = &mut ctx.accounts.pool;
 
+    // The referral fee is carved out of the input before it reaches the pool
+    let referral_fee = ctx
+        .accounts
+        .dex_configuration_account
+        .referral_fee(amount, referral_fee_bps)?;
+    let swap_amount = amount
+        .checked_sub(referral_fee)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    if referral_fee_bps > 0 {
+        let (Some(referrer_token_account), Some(referrer_stats)) = (
+            ctx.accounts.referrer_token_account.as_ref(),
+            ctx.accounts.referrer_stats.as_mut(),
+        ) else {
+            return err!(DexProgramError::InvalidReferrer);
+        };
+
+        if !referrer_token_account.owner.eq(&referrer_stats.referrer) {
+            return err!(DexProgramError::InvalidReferrer);
+        }
+
+        // Paid straight from the user to the referrer, it never passes the pool.
+        // A native SOL input is wrapped into the referrer's wrapped SOL account.
+        if referral_fee > 0 {
+            if native_sol && ctx.accounts.mint_token_one.key().eq(&native_mint::ID) {
+                wrap_sol_from_user(
+                    &ctx.accounts.user,
+                    referrer_token_account,
+                    referral_fee,
+                    &ctx.accounts.system_program,
+                    &ctx.accounts.token_program,
+                )?;
+            } else {
+                let Some(user_token_account_one) = ctx.accounts.user_token_account_one.as_ref()
+                else {
+                    return err!(DexProgramError::MissingTokenAccount);
+                };
+
+                transfer_from_user(
+                    user_token_account_one,
+                    referrer_token_account,
+                    referral_fee,
+                    &ctx.accounts.user,
+                    &ctx.accounts.token_program,
+                )?;
+            }
+        }
+
+        referrer_stats.record(amount, referral_fee)?;
+    }
+
+    // Stakers of the governance token trade at a discount, without a stake account
+    // the full fee applies.
+    let fee_discount_bps = match ctx.accounts.stake_account.as_ref() {
+        Some(stake_account) => ctx
+            .accounts
+            .dex_configuration_account
+            .fee_discount_bps(stake_account.active_stake(Clock::get()?.unix_timestamp)),
+        None => 0_u16,
+    };
+
+    // A native SOL leg settles through the temporary wrapped SOL account, the
+    // user needs no token account for it
+    let (native_sol_account_one, native_sol_account_two) = native_sol_legs(
+        native_sol,
+        ctx.accounts.native_sol_account.as_deref_mut(),
+        &ctx.accounts.mint_token_one,
+    )?;
+
     let token_one_accounts = (
         &mut *ctx.accounts.mint_token_one,
         &mut *ctx.accounts.pool_token_account_one,
-        &mut *ctx.accounts.user_token_account_one,
+        settlement_account(
+            native_sol_account_one,
+            ctx.accounts.user_token_account_one.as_deref_mut(),
+        )?,
     );
 
+    // Routers may direct the output to any token account of the right mint. Such
+    // a recipient is paid in wrapped SOL even with `native_sol`, only the signer's
+    // own output is unwrapped.
+    let output_token_account = match ctx.accounts.recipient_token_account.as_mut() {
+        Some(recipient_token_account) => &mut **recipient_token_account,
+        None => settlement_account(
+            native_sol_account_two,
+            ctx.accounts.user_token_account_two.as_deref_mut(),
+        )?,
+    };
+
     let token_two_accounts = (
         &mut *ctx.accounts.mint_token_two,
         &mut *ctx.accounts.pool_token_account_two,
-        &mut *ctx.accounts.user_token_account_two,
+        output_token_account,
     );
 
-    pool.swap(
-        &*ctx.accounts.dex_configuration_account,
+    let mut swap_amounts = pool.swap(
+        &ctx.accounts.dex_configuration_account,
         token_one_accounts,
         token_two_accounts,
-        amount,
+        swap_amount,
+        fee_discount_bps,
+        native_sol,
         &ctx.accounts.user,
         &ctx.accounts.token_program,
+        &ctx.accounts.system_program,
+    )?;
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
     )?;
 
-    Ok(())
+    // Closing the temporary wrapped SOL account pays out its lamports, the rent
+    // and any unwrapped SOL, to the user
+    if let Some(native_sol_account) = ctx.accounts.native_sol_account.as_ref() {
+        pool.close_pool_token_account(
+            native_sol_account,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    // Report everything the user paid, including the referrer's cut
+    swap_amounts.amount_in = amount;
+    swap_amounts.referral_fee = referral_fee;
+
+    Ok(swap_amounts)
 }
 
 #[derive(Accounts)]
@@ -48,7 +169,8 @@ pub struct Swap<'info> {
     #[account(
         mut,
         seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
-        bump = pool.bump
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
     )]
     pub pool: Box<Account<'info, LiquidityPool>>,
 
@@ -77,18 +199,60 @@ pub struct Swap<'info> {
     pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_token_one,
         associated_token::authority = user,
     )]
-    pub user_token_account_one: Box<Account<'info, TokenAccount>>,
+    pub user_token_account_one: Option<Box<Account<'info, TokenAccount>>>,
 
     #[account(
-        mut,
+        init_if_needed,
+        payer = user,
         associated_token::mint = mint_token_two,
         associated_token::authority = user,
     )]
-    pub user_token_account_two: Box<Account<'info, TokenAccount>>,
+    pub user_token_account_two: Option<Box<Account<'info, TokenAccount>>>,
+
+    // Temporary wrapped SOL account a native SOL leg settles through. It is owned
+    // by the pool and closed again before the instruction ends.
+    #[account(
+        init,
+        payer = user,
+        seeds = [LiquidityPool::NATIVE_SOL_SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump,
+        token::mint = native_mint,
+        token::authority = pool,
+    )]
+    pub native_sol_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(address = native_mint::ID)]
+    pub native_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub recipient_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_one,
+    )]
+    pub referrer_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        seeds = [ReferrerStats::SEED_PREFIX.as_bytes(), referrer_stats.referrer.as_ref(), mint_token_one.key().as_ref()],
+        bump = referrer_stats.bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+
+    #[account(
+        seeds = [StakeAccount::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump = stake_account.bump,
+    )]
+    pub stake_account: Option<Box<Account<'info, StakeAccount>>>,
 
     #[account(mut)]
     pub user: Signer<'info>,
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+use crate::{
+    errors::DexProgramError,
+    state::{DexConfiguration, LiquidityPool, LiquidityPoolAccount},
+};
+
+pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    // Long-term orders have to settle first so their balances are current
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let (balance_one, balance_two) = if pool.is_token_one(ctx.accounts.mint_token_one.key()) {
+        (
+            ctx.accounts.pool_token_account_one.amount,
+            ctx.accounts.pool_token_account_two.amount,
+        )
+    } else {
+        (
+            ctx.accounts.pool_token_account_two.amount,
+            ctx.accounts.pool_token_account_one.amount,
+        )
+    };
+
+    // Unclaimed fees and long-term order balances share the vaults with the
+    // reserves but are not part of them. A vault holding less than those is
+    // insolvent and must not be papered over with empty reserves.
+    let reserve_one = balance_one
+        .checked_sub(pool.fee_reserve_one)
+        .and_then(|balance| balance.checked_sub(pool.twamm.balances[0]))
+        .ok_or(DexProgramError::VaultBalanceBelowReserves)?;
+    let reserve_two = balance_two
+        .checked_sub(pool.fee_reserve_two)
+        .and_then(|balance| balance.checked_sub(pool.twamm.balances[1]))
+        .ok_or(DexProgramError::VaultBalanceBelowReserves)?;
+
+    pool.update_reserves(reserve_one, reserve_two)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SyncReserves<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        constraint = !mint_token_one.key().eq(&mint_token_two.key()) @ DexProgramError::DuplicateTokenNotAllowed
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+}
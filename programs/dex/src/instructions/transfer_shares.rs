@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityPoolAccount, LiquidityProvider},
+};
+
+pub fn transfer_shares(ctx: Context<TransferShares>, shares: u64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+
+    if ctx.accounts.destination_liquidity_provider_account.payer == Pubkey::default() {
+        ctx.accounts.destination_liquidity_provider_account.payer = ctx.accounts.user.key();
+        ctx.accounts.destination_liquidity_provider_account.version = LiquidityProvider::VERSION;
+    }
+
+    pool.transfer_shares(
+        &mut ctx.accounts.liquidity_provider_account,
+        &mut ctx.accounts.destination_liquidity_provider_account,
+        shares,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferShares<'info> {
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LiquidityProvider::ACCOUNT_SIZE,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), destination_owner.key().as_ref()],
+        bump,
+    )]
+    pub destination_liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    /// CHECK: Any wallet or program address may own liquidity provider shares
+    #[account(
+        constraint = !destination_owner.key().eq(&user.key()) @ DexProgramError::InvalidShareRecipient
+    )]
+    pub destination_owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,138 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+pub fn trigger_order(ctx: Context<ExecuteTriggerOrder>) -> Result<SwapAmounts> {
+    let pool = &mut ctx.accounts.pool;
+    let order = &ctx.accounts.trigger_order;
+    let amount_in = order.amount_in;
+
+    // The trigger uses the TWAP so a single manipulated swap cannot set it off,
+    // the execution itself is protected by the minimum output.
+    let twap = pool.twap(Clock::get()?.unix_timestamp, TriggerOrder::TWAP_WINDOW)?;
+    let price = if pool.is_token_one(order.input_mint) {
+        twap
+    } else {
+        1_f64 / twap
+    };
+
+    if !order.is_triggered(price) {
+        return err!(DexProgramError::TriggerPriceNotReached);
+    }
+
+    let swap_amounts = order.execute_from_escrow(
+        pool,
+        &ctx.accounts.dex_configuration_account,
+        &ctx.accounts.escrow,
+        (
+            &ctx.accounts.mint_token_one,
+            &mut ctx.accounts.pool_token_account_one,
+        ),
+        &mut ctx.accounts.pool_token_account_two,
+        (
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.keeper_token_account,
+        ),
+        amount_in,
+        order.min_amount_out,
+        &ctx.accounts.token_program,
+    )?;
+
+    // Trigger orders always execute in full, the rent goes back to the owner
+    // along with anything else that was sent to the escrow
+    order.refund_and_close_escrow(
+        &ctx.accounts.escrow,
+        amount_in,
+        ctx.accounts.owner_input_token_account.as_deref(),
+        &ctx.accounts.owner.to_account_info(),
+        &ctx.accounts.token_program,
+    )?;
+
+    Ok(swap_amounts)
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTriggerOrder<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_SWAPS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [TriggerOrder::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &trigger_order.order_id.to_le_bytes()],
+        bump = trigger_order.bump,
+        has_one = pool,
+        has_one = owner,
+        close = owner,
+    )]
+    pub trigger_order: Box<Account<'info, TriggerOrder>>,
+
+    #[account(
+        mut,
+        seeds = [TriggerOrder::ESCROW_SEED_PREFIX.as_bytes(), trigger_order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        address = trigger_order.input_mint,
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        address = trigger_order.output_mint,
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    // Only needed when the escrow holds more than the order sells
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = owner,
+    )]
+    pub owner_input_token_account: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        token::mint = mint_token_two,
+    )]
+    pub keeper_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    pub keeper: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,91 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+
+pub fn vote_gauge(ctx: Context<VoteGauge>, vote_bps: u16) -> Result<()> {
+    let gauge_controller = &mut ctx.accounts.gauge_controller;
+    let gauge = &mut ctx.accounts.gauge;
+    let vote_escrow = &mut ctx.accounts.vote_escrow;
+    let gauge_vote = &mut ctx.accounts.gauge_vote;
+
+    let now = Clock::get()?.unix_timestamp;
+    let epoch = gauge_controller.epoch_at(now);
+
+    if vote_escrow.vote_epoch != epoch {
+        vote_escrow.vote_epoch = epoch;
+        vote_escrow.used_vote_bps = 0_u16;
+    }
+
+    // Voting again for the same gauge in an epoch replaces the earlier vote
+    if gauge_vote.epoch == epoch {
+        gauge.weights.sub(epoch, gauge_vote.weight)?;
+        gauge_controller
+            .total_weights
+            .sub(epoch, gauge_vote.weight)?;
+        vote_escrow.used_vote_bps = vote_escrow
+            .used_vote_bps
+            .saturating_sub(gauge_vote.vote_bps);
+    }
+
+    let used_vote_bps = vote_escrow
+        .used_vote_bps
+        .checked_add(vote_bps)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+    if used_vote_bps > 10_000 {
+        return err!(DexProgramError::VotingPowerExceeded);
+    }
+
+    let weight = (vote_escrow.voting_power(now) as u128)
+        .checked_mul(vote_bps as u128)
+        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+        / 10_000_u128;
+
+    gauge.weights.add(epoch, weight)?;
+    gauge_controller.total_weights.add(epoch, weight)?;
+
+    vote_escrow.used_vote_bps = used_vote_bps;
+
+    gauge_vote.epoch = epoch;
+    gauge_vote.weight = weight;
+    gauge_vote.vote_bps = vote_bps;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VoteGauge<'info> {
+    #[account(
+        mut,
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        mut,
+        seeds = [Gauge::SEED_PREFIX.as_bytes(), gauge.pool.as_ref()],
+        bump = gauge.bump,
+    )]
+    pub gauge: Box<Account<'info, Gauge>>,
+
+    #[account(
+        mut,
+        seeds = [VoteEscrow::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Box<Account<'info, VoteEscrow>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = GaugeVote::ACCOUNT_SIZE,
+        seeds = [GaugeVote::SEED_PREFIX.as_bytes(), gauge.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub gauge_vote: Box<Account<'info, GaugeVote>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,113 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+pub fn withdraw_from_vault(ctx: Context<WithdrawFromVault>, vault_shares: u64) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let vault_liquidity_provider_account = &mut ctx.accounts.vault_liquidity_provider_account;
+
+    let now = Clock::get()?.unix_timestamp;
+
+    pool.execute_virtual_orders(&ctx.accounts.dex_configuration_account, now)?;
+
+    // Vault shares are valued the same way as on deposit, unharvested earnings
+    // included. They are paid out of the position's shares, which the earnings
+    // replace once they are harvested.
+    pool.update_rewards(now)?;
+    pool.accrue_fees(vault_liquidity_provider_account)?;
+    pool.accrue_rewards(vault_liquidity_provider_account)?;
+
+    let vault_liquidity_shares =
+        CompoundingVault::liquidity_shares(pool, vault_liquidity_provider_account)?;
+
+    let shares = CompoundingVault::shares_for_withdrawal(
+        vault_shares,
+        vault_liquidity_shares,
+        ctx.accounts.share_mint.supply,
+    )?
+    .min(vault_liquidity_provider_account.shares);
+
+    if shares == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    if ctx.accounts.liquidity_provider_account.payer == Pubkey::default() {
+        ctx.accounts.liquidity_provider_account.payer = ctx.accounts.user.key();
+        ctx.accounts.liquidity_provider_account.version = LiquidityProvider::VERSION;
+    }
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token::Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        vault_shares,
+    )?;
+
+    ctx.accounts.pool.transfer_shares(
+        &mut ctx.accounts.vault_liquidity_provider_account,
+        &mut ctx.accounts.liquidity_provider_account,
+        shares,
+    )?;
+
+    Ok(shares)
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromVault<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(pool.token_one.key(), pool.token_two.key()).as_bytes()],
+        bump = pool.bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        seeds = [CompoundingVault::SEED_PREFIX.as_bytes(), pool.key().as_ref()],
+        bump = vault.bump,
+        has_one = share_mint,
+    )]
+    pub vault: Box<Account<'info, CompoundingVault>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = LiquidityProvider::ACCOUNT_SIZE,
+        seeds = [LiquidityProvider::SEED_PREFIX.as_bytes(), pool.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_provider_account: Box<Account<'info, LiquidityProvider>>,
+
+    #[account(mut)]
+    pub share_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = user,
+    )]
+    pub user_share_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
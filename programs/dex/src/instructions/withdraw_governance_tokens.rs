@@ -0,0 +1,60 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+pub fn withdraw_governance_tokens(ctx: Context<WithdrawGovernanceTokens>) -> Result<()> {
+    let vote_escrow = &mut ctx.accounts.vote_escrow;
+
+    if Clock::get()?.unix_timestamp < vote_escrow.lock_end {
+        return err!(DexProgramError::GovernanceTokensLocked);
+    }
+
+    if vote_escrow.amount == 0 {
+        return err!(DexProgramError::InvalidAmount);
+    }
+
+    ctx.accounts.gauge_controller.transfer_from_controller(
+        &ctx.accounts.locker_vault,
+        &ctx.accounts.user_token_account,
+        vote_escrow.amount,
+        &ctx.accounts.token_program,
+    )?;
+
+    vote_escrow.amount = 0_u64;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawGovernanceTokens<'info> {
+    #[account(
+        seeds = [GaugeController::SEED.as_bytes()],
+        bump = gauge_controller.bump,
+    )]
+    pub gauge_controller: Box<Account<'info, GaugeController>>,
+
+    #[account(
+        mut,
+        seeds = [VoteEscrow::SEED_PREFIX.as_bytes(), user.key().as_ref()],
+        bump = vote_escrow.bump,
+    )]
+    pub vote_escrow: Box<Account<'info, VoteEscrow>>,
+
+    #[account(
+        mut,
+        seeds = [GaugeController::LOCKER_VAULT_SEED.as_bytes()],
+        bump,
+    )]
+    pub locker_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = gauge_controller.governance_mint,
+        token::authority = user,
+    )]
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -0,0 +1,186 @@
+use crate::{errors::DexProgramError, state::*};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+// Pays out the proceeds accrued so far. Anyone may crank this, the proceeds
+// always go to the owner and an expired order is closed once settled.
+pub fn withdraw_long_term_order(ctx: Context<WithdrawLongTermOrder>) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let order = &mut ctx.accounts.long_term_order;
+
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let (side, output_side) = if pool.is_token_one(order.input_mint) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let proceeds = order.take_proceeds(&pool.twamm, side)?;
+    let is_expired = order.is_expired(&pool.twamm);
+
+    if proceeds == 0 && !is_expired {
+        return err!(DexProgramError::NothingToWithdraw);
+    }
+
+    if proceeds > 0 {
+        pool.twamm.debit(output_side, proceeds)?;
+        pool.transfer_token_from_pool(
+            &ctx.accounts.pool_token_account_two,
+            &ctx.accounts.owner_token_account_two,
+            proceeds,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    if is_expired {
+        pool.twamm
+            .remove_order(side, order.sell_rate, order.expiry_interval)?;
+        order.close(ctx.accounts.owner.to_account_info())?;
+        pool.sweep_twamm_dust()?;
+    }
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    Ok(proceeds)
+}
+
+// Stops the order, returning the unsold input together with the proceeds so far
+pub fn cancel_long_term_order(ctx: Context<WithdrawLongTermOrder>) -> Result<u64> {
+    let pool = &mut ctx.accounts.pool;
+    let order = &mut ctx.accounts.long_term_order;
+
+    if !ctx.accounts.authority.key().eq(&order.owner) {
+        return err!(DexProgramError::Unauthorized);
+    }
+
+    pool.execute_virtual_orders(
+        &ctx.accounts.dex_configuration_account,
+        Clock::get()?.unix_timestamp,
+    )?;
+
+    let (side, output_side) = if pool.is_token_one(order.input_mint) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    let proceeds = order.take_proceeds(&pool.twamm, side)?;
+    let unsold_amount = order.unsold_amount(&pool.twamm)?;
+
+    pool.twamm
+        .remove_order(side, order.sell_rate, order.expiry_interval)?;
+
+    if unsold_amount > 0 {
+        pool.twamm.debit(side, unsold_amount)?;
+        pool.transfer_token_from_pool(
+            &ctx.accounts.pool_token_account_one,
+            &ctx.accounts.owner_token_account_one,
+            unsold_amount,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    if proceeds > 0 {
+        pool.twamm.debit(output_side, proceeds)?;
+        pool.transfer_token_from_pool(
+            &ctx.accounts.pool_token_account_two,
+            &ctx.accounts.owner_token_account_two,
+            proceeds,
+            &ctx.accounts.token_program,
+        )?;
+    }
+
+    order.close(ctx.accounts.owner.to_account_info())?;
+    pool.sweep_twamm_dust()?;
+
+    pool.check_vault_balances(
+        &mut ctx.accounts.pool_token_account_one,
+        &mut ctx.accounts.pool_token_account_two,
+    )?;
+
+    Ok(proceeds)
+}
+
+#[derive(Accounts)]
+pub struct WithdrawLongTermOrder<'info> {
+    #[account(
+        seeds = [DexConfiguration::SEED.as_bytes()],
+        bump,
+    )]
+    pub dex_configuration_account: Box<Account<'info, DexConfiguration>>,
+
+    #[account(
+        mut,
+        seeds = [LiquidityPool::POOL_SEED_PREFIX.as_bytes(), LiquidityPool::generate_seed(mint_token_one.key(), mint_token_two.key()).as_bytes()],
+        bump = pool.bump,
+        constraint = !pool.is_paused(&dex_configuration_account, PAUSE_WITHDRAWALS) @ DexProgramError::OperationPaused
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [LongTermOrder::SEED_PREFIX.as_bytes(), pool.key().as_ref(), owner.key().as_ref(), &long_term_order.order_id.to_le_bytes()],
+        bump = long_term_order.bump,
+        has_one = pool,
+        has_one = owner,
+    )]
+    pub long_term_order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(
+        address = long_term_order.input_mint,
+    )]
+    pub mint_token_one: Box<Account<'info, Mint>>,
+
+    #[account(
+        address = long_term_order.output_mint,
+    )]
+    pub mint_token_two: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = pool
+    )]
+    pub pool_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint_token_one,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account_one: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = mint_token_two,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account_two: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
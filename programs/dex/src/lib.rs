@@ -6,6 +6,9 @@ pub mod instructions;
 pub mod state;
 
 use crate::instructions::*;
+use crate::state::{
+    FeeAmounts, FeeDiscountTier, LiquidityAmounts, RfqQuote, RfqSwapAmounts, SwapAmounts,
+};
 
 declare_id!("HHtpy5cez4guhvwoXVCZzo8EUce6ouJyXaxZ7r9CVR24");
 
@@ -25,15 +28,360 @@ pub mod dex {
         ctx: Context<AddLiquidity>,
         amount_one: u64,
         amount_two: u64,
+        native_sol: bool,
+    ) -> Result<LiquidityAmounts> {
+        instructions::add_liquidity(ctx, amount_one, amount_two, native_sol)
+    }
+
+    pub fn remove_liquidity(
+        ctx: Context<RemoveLiquidity>,
+        shares: u64,
+        native_sol: bool,
+    ) -> Result<LiquidityAmounts> {
+        instructions::remove_liquidity(ctx, shares, native_sol)
+    }
+
+    pub fn swap(
+        ctx: Context<Swap>,
+        amount: u64,
+        native_sol: bool,
+        deadline: Option<i64>,
+        referral_fee_bps: u16,
+    ) -> Result<SwapAmounts> {
+        instructions::swap(ctx, amount, native_sol, deadline, referral_fee_bps)
+    }
+
+    pub fn configure_dynamic_fee(
+        ctx: Context<ConfigureDynamicFee>,
+        enabled: bool,
+        min_fee: f64,
+        max_fee: f64,
+    ) -> Result<()> {
+        instructions::configure_dynamic_fee(ctx, enabled, min_fee, max_fee)
+    }
+
+    pub fn quote_swap_exact_in(
+        ctx: Context<Quote>,
+        amount_in: u64,
+        referral_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::quote_swap_exact_in(ctx, amount_in, referral_fee_bps)
+    }
+
+    pub fn quote_swap_exact_out(
+        ctx: Context<Quote>,
+        amount_out: u64,
+        referral_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::quote_swap_exact_out(ctx, amount_out, referral_fee_bps)
+    }
+
+    pub fn quote_add_liquidity(
+        ctx: Context<Quote>,
+        amount_one: u64,
+        amount_two: u64,
     ) -> Result<()> {
-        instructions::add_liquidity(ctx, amount_one, amount_two)
+        instructions::quote_add_liquidity(ctx, amount_one, amount_two)
+    }
+
+    pub fn quote_remove_liquidity(ctx: Context<Quote>, shares: u64) -> Result<()> {
+        instructions::quote_remove_liquidity(ctx, shares)
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian(ctx, guardian)
+    }
+
+    pub fn pause_dex(ctx: Context<PauseDex>, flags: u8) -> Result<()> {
+        instructions::pause_dex(ctx, flags)
+    }
+
+    pub fn unpause_dex(ctx: Context<PauseDex>, flags: u8) -> Result<()> {
+        instructions::unpause_dex(ctx, flags)
+    }
+
+    pub fn pause_pool(ctx: Context<PausePool>, flags: u8) -> Result<()> {
+        instructions::pause_pool(ctx, flags)
+    }
+
+    pub fn unpause_pool(ctx: Context<PausePool>, flags: u8) -> Result<()> {
+        instructions::unpause_pool(ctx, flags)
+    }
+
+    pub fn configure_circuit_breaker(
+        ctx: Context<ConfigureCircuitBreaker>,
+        max_slot_price_movement_bps: u16,
+        max_window_price_movement_bps: u16,
+        price_window_slots: u64,
+    ) -> Result<()> {
+        instructions::configure_circuit_breaker(
+            ctx,
+            max_slot_price_movement_bps,
+            max_window_price_movement_bps,
+            price_window_slots,
+        )
+    }
+
+    pub fn configure_trade_limits(
+        ctx: Context<ConfigureTradeLimits>,
+        max_trade_size_bps: u16,
+        max_price_impact_bps: u16,
+    ) -> Result<()> {
+        instructions::configure_trade_limits(ctx, max_trade_size_bps, max_price_impact_bps)
+    }
+
+    pub fn sync(ctx: Context<SyncReserves>) -> Result<()> {
+        instructions::sync(ctx)
+    }
+
+    pub fn skim(ctx: Context<SkimReserves>) -> Result<()> {
+        instructions::skim(ctx)
+    }
+
+    pub fn close_liquidity_provider(ctx: Context<CloseLiquidityProvider>) -> Result<()> {
+        instructions::close_liquidity_provider(ctx)
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>) -> Result<()> {
+        instructions::close_pool(ctx)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config(ctx)
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        instructions::migrate_pool(ctx)
+    }
+
+    pub fn migrate_liquidity_provider(ctx: Context<MigrateLiquidityProvider>) -> Result<()> {
+        instructions::migrate_liquidity_provider(ctx)
+    }
+
+    pub fn transfer_shares(ctx: Context<TransferShares>, shares: u64) -> Result<()> {
+        instructions::transfer_shares(ctx, shares)
+    }
+
+    pub fn approve_delegate(
+        ctx: Context<ManageDelegate>,
+        delegate: Pubkey,
+        delegated_shares: u64,
+    ) -> Result<()> {
+        instructions::approve_delegate(ctx, delegate, delegated_shares)
+    }
+
+    pub fn revoke_delegate(ctx: Context<ManageDelegate>) -> Result<()> {
+        instructions::revoke_delegate(ctx)
+    }
+
+    pub fn lock_shares(ctx: Context<LockShares>, shares: u64, unlock_timestamp: i64) -> Result<()> {
+        instructions::lock_shares(ctx, shares, unlock_timestamp)
+    }
+
+    pub fn extend_lock(ctx: Context<LockShares>, unlock_timestamp: i64) -> Result<()> {
+        instructions::extend_lock(ctx, unlock_timestamp)
+    }
+
+    pub fn claim_fees(ctx: Context<ClaimFees>, native_sol: bool) -> Result<FeeAmounts> {
+        instructions::claim_fees(ctx, native_sol)
+    }
+
+    pub fn set_emission_rate(
+        ctx: Context<SetEmissionRate>,
+        reward_index: u8,
+        emission_rate: u64,
+        end_time: i64,
+    ) -> Result<()> {
+        instructions::set_emission_rate(ctx, reward_index, emission_rate, end_time)
+    }
+
+    pub fn fund_rewards(ctx: Context<FundRewards>, reward_index: u8, amount: u64) -> Result<()> {
+        instructions::fund_rewards(ctx, reward_index, amount)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, reward_index: u8) -> Result<u64> {
+        instructions::claim_rewards(ctx, reward_index)
+    }
+
+    pub fn close_reward_vault(ctx: Context<CloseRewardVault>, reward_index: u8) -> Result<()> {
+        instructions::close_reward_vault(ctx, reward_index)
+    }
+
+    pub fn initialize_gauge_controller(
+        ctx: Context<InitializeGaugeController>,
+        epoch_duration: i64,
+        emissions_per_epoch: u64,
+    ) -> Result<()> {
+        instructions::initialize_gauge_controller(ctx, epoch_duration, emissions_per_epoch)
+    }
+
+    pub fn create_gauge(ctx: Context<CreateGauge>) -> Result<()> {
+        instructions::create_gauge(ctx)
+    }
+
+    pub fn lock_governance_tokens(
+        ctx: Context<LockGovernanceTokens>,
+        amount: u64,
+        unlock_time: i64,
+    ) -> Result<()> {
+        instructions::lock_governance_tokens(ctx, amount, unlock_time)
+    }
+
+    pub fn withdraw_governance_tokens(ctx: Context<WithdrawGovernanceTokens>) -> Result<()> {
+        instructions::withdraw_governance_tokens(ctx)
+    }
+
+    pub fn vote_gauge(ctx: Context<VoteGauge>, vote_bps: u16) -> Result<()> {
+        instructions::vote_gauge(ctx, vote_bps)
+    }
+
+    pub fn distribute_emissions(ctx: Context<DistributeEmissions>, reward_index: u8) -> Result<()> {
+        instructions::distribute_emissions(ctx, reward_index)
+    }
+
+    pub fn create_compounding_vault(ctx: Context<CreateCompoundingVault>) -> Result<()> {
+        instructions::create_compounding_vault(ctx)
+    }
+
+    pub fn deposit_to_vault(ctx: Context<DepositToVault>, shares: u64) -> Result<u64> {
+        instructions::deposit_to_vault(ctx, shares)
+    }
+
+    pub fn withdraw_from_vault(ctx: Context<WithdrawFromVault>, vault_shares: u64) -> Result<u64> {
+        instructions::withdraw_from_vault(ctx, vault_shares)
+    }
+
+    pub fn harvest(
+        ctx: Context<Harvest>,
+        reward_index: Option<u8>,
+        min_shares_out: u64,
+    ) -> Result<u64> {
+        instructions::harvest(ctx, reward_index, min_shares_out)
+    }
+
+    pub fn configure_referral_fee(
+        ctx: Context<ConfigureReferralFee>,
+        max_referral_fee_bps: u16,
+    ) -> Result<()> {
+        instructions::configure_referral_fee(ctx, max_referral_fee_bps)
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        instructions::register_referrer(ctx)
+    }
+
+    pub fn configure_fee_discounts(
+        ctx: Context<ConfigureFeeDiscounts>,
+        tiers: Vec<FeeDiscountTier>,
+    ) -> Result<()> {
+        instructions::configure_fee_discounts(ctx, tiers)
+    }
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        instructions::stake(ctx, amount)
+    }
+
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        instructions::unstake(ctx, amount)
+    }
+
+    pub fn place_order(
+        ctx: Context<PlaceOrder>,
+        order_id: u64,
+        amount_in: u64,
+        limit_price: f64,
+        expiry: i64,
+    ) -> Result<()> {
+        instructions::place_order(ctx, order_id, amount_in, limit_price, expiry)
+    }
+
+    pub fn fill_order(ctx: Context<FillOrder>, amount_in: u64) -> Result<SwapAmounts> {
+        instructions::fill_order(ctx, amount_in)
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        instructions::cancel_order(ctx)
+    }
+
+    pub fn place_long_term_order(
+        ctx: Context<PlaceLongTermOrder>,
+        order_id: u64,
+        amount_in: u64,
+        intervals: u64,
+    ) -> Result<()> {
+        instructions::place_long_term_order(ctx, order_id, amount_in, intervals)
+    }
+
+    pub fn withdraw_long_term_order(ctx: Context<WithdrawLongTermOrder>) -> Result<u64> {
+        instructions::withdraw_long_term_order(ctx)
+    }
+
+    pub fn cancel_long_term_order(ctx: Context<WithdrawLongTermOrder>) -> Result<u64> {
+        instructions::cancel_long_term_order(ctx)
+    }
+
+    pub fn open_dca_position(
+        ctx: Context<OpenDcaPosition>,
+        position_id: u64,
+        amount_per_cycle: u64,
+        cycle_interval: i64,
+        cycles: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::open_dca_position(
+            ctx,
+            position_id,
+            amount_per_cycle,
+            cycle_interval,
+            cycles,
+            min_amount_out,
+        )
+    }
+
+    pub fn execute_dca_cycle(ctx: Context<ExecuteDcaCycle>) -> Result<SwapAmounts> {
+        instructions::execute_dca_cycle(ctx)
+    }
+
+    pub fn close_dca_position(ctx: Context<CloseDcaPosition>) -> Result<()> {
+        instructions::close_dca_position(ctx)
+    }
+
+    pub fn place_trigger_order(
+        ctx: Context<PlaceTriggerOrder>,
+        order_id: u64,
+        amount_in: u64,
+        trigger_price: f64,
+        is_take_profit: bool,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::place_trigger_order(
+            ctx,
+            order_id,
+            amount_in,
+            trigger_price,
+            is_take_profit,
+            min_amount_out,
+        )
+    }
+
+    pub fn trigger_order(ctx: Context<ExecuteTriggerOrder>) -> Result<SwapAmounts> {
+        instructions::trigger_order(ctx)
+    }
+
+    pub fn cancel_trigger_order(ctx: Context<CancelTriggerOrder>) -> Result<()> {
+        instructions::cancel_trigger_order(ctx)
     }
 
-    pub fn remove_liquidity(ctx: Context<RemoveLiquidity>, shares: u64) -> Result<()> {
-        instructions::remove_liquidity(ctx, shares)
+    pub fn register_rfq_maker(ctx: Context<RegisterRfqMaker>, page: u64) -> Result<()> {
+        instructions::register_rfq_maker(ctx, page)
     }
 
-    pub fn swap(ctx: Context<Swap>, amount: u64) -> Result<()> {
-        instructions::swap(ctx, amount)
+    pub fn rfq_swap(
+        ctx: Context<RfqSwap>,
+        amount_in: u64,
+        min_amount_out: u64,
+        quote: Option<RfqQuote>,
+    ) -> Result<RfqSwapAmounts> {
+        instructions::rfq_swap(ctx, amount_in, min_amount_out, quote)
     }
 }
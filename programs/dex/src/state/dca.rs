@@ -0,0 +1,56 @@
+use crate::state::EscrowedOrder;
+use anchor_lang::prelude::*;
+
+// A recurring buy selling `amount_per_cycle` of the input mint into the pool
+// once every `cycle_interval` seconds until no cycles remain.
+#[account]
+pub struct DcaPosition {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub position_id: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_per_cycle: u64,
+    pub cycle_interval: i64,
+    pub cycles_remaining: u64,
+    pub min_amount_out: u64,
+    pub next_cycle_at: i64,
+    pub bump: u8,
+}
+
+impl DcaPosition {
+    pub const SEED_PREFIX: &'static str = "dca_position";
+
+    pub const ESCROW_SEED_PREFIX: &'static str = "dca_escrow";
+
+    // Discriminator (8) + owner (32) + pool (32) + position id (8) + input mint (32)
+    // + output mint (32) + amount per cycle (8) + cycle interval (8)
+    // + cycles remaining (8) + min amount out (8) + next cycle at (8) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn is_cycle_due(&self, now: i64) -> bool {
+        self.cycles_remaining > 0 && now >= self.next_cycle_at
+    }
+}
+
+impl EscrowedOrder for DcaPosition {
+    fn seed_prefix() -> &'static str {
+        Self::SEED_PREFIX
+    }
+
+    fn pool_key(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn owner_key(&self) -> Pubkey {
+        self.owner
+    }
+
+    fn id(&self) -> u64 {
+        self.position_id
+    }
+
+    fn bump(&self) -> u8 {
+        self.bump
+    }
+}
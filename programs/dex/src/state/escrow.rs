@@ -0,0 +1,188 @@
+use crate::errors::DexProgramError;
+use crate::state::{DexConfiguration, LiquidityPool, LiquidityPoolAccount, SwapAmounts};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+// Share of the output paid to the keeper executing an escrowed order
+pub const KEEPER_FEE_BPS: u64 = 10;
+
+pub fn keeper_fee(amount_out: u64) -> u64 {
+    ((amount_out as u128) * (KEEPER_FEE_BPS as u128) / 10_000_u128) as u64
+}
+
+// An order whose input waits in an escrow token account until a keeper executes
+// it. The order account is derived from its pool, owner and id and signs for
+// the escrow.
+pub trait EscrowedOrder {
+    fn seed_prefix() -> &'static str;
+
+    fn pool_key(&self) -> Pubkey;
+
+    fn owner_key(&self) -> Pubkey;
+
+    fn id(&self) -> u64;
+
+    fn bump(&self) -> u8;
+}
+
+pub trait EscrowAccount<'info> {
+    fn transfer_from_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    fn close_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    // Returns whatever the escrow still holds to the owner and closes it, tokens
+    // anyone sent to it included. `spent` is what left the escrow earlier in the
+    // same instruction, the loaded balance does not reflect it.
+    fn refund_and_close_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        spent: u64,
+        owner_token_account: Option<&Account<'info, TokenAccount>>,
+        owner: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    // Swaps `amount_in` of the escrow through the pool, the same path a regular
+    // trade takes. The keeper is paid its fee out of the output.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_from_escrow(
+        &self,
+        pool: &mut Account<'info, LiquidityPool>,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        escrow: &Account<'info, TokenAccount>,
+        token_one_accounts: (&Account<'info, Mint>, &mut Account<'info, TokenAccount>),
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+        recipient_accounts: (&Account<'info, TokenAccount>, &Account<'info, TokenAccount>),
+        amount_in: u64,
+        min_amount_out: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<SwapAmounts>;
+}
+
+impl<'info, T> EscrowAccount<'info> for Account<'info, T>
+where
+    T: EscrowedOrder + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    fn transfer_from_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        let (pool, owner, id) = (self.pool_key(), self.owner_key(), self.id().to_le_bytes());
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: escrow.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.to_account_info(),
+                },
+                &[&[
+                    T::seed_prefix().as_bytes(),
+                    pool.as_ref(),
+                    owner.as_ref(),
+                    &id,
+                    &[self.bump()],
+                ]],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    fn close_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        let (pool, owner, id) = (self.pool_key(), self.owner_key(), self.id().to_le_bytes());
+
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::CloseAccount {
+                account: escrow.to_account_info(),
+                destination: destination.to_account_info(),
+                authority: self.to_account_info(),
+            },
+            &[&[
+                T::seed_prefix().as_bytes(),
+                pool.as_ref(),
+                owner.as_ref(),
+                &id,
+                &[self.bump()],
+            ]],
+        ))?;
+
+        Ok(())
+    }
+
+    fn refund_and_close_escrow(
+        &self,
+        escrow: &Account<'info, TokenAccount>,
+        spent: u64,
+        owner_token_account: Option<&Account<'info, TokenAccount>>,
+        owner: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        let remaining_amount = escrow.amount.saturating_sub(spent);
+
+        if remaining_amount > 0 {
+            let Some(owner_token_account) = owner_token_account else {
+                return err!(DexProgramError::MissingTokenAccount);
+            };
+
+            self.transfer_from_escrow(
+                escrow,
+                owner_token_account,
+                remaining_amount,
+                token_program,
+            )?;
+        }
+
+        self.close_escrow(escrow, owner, token_program)
+    }
+
+    fn execute_from_escrow(
+        &self,
+        pool: &mut Account<'info, LiquidityPool>,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        escrow: &Account<'info, TokenAccount>,
+        token_one_accounts: (&Account<'info, Mint>, &mut Account<'info, TokenAccount>),
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+        recipient_accounts: (&Account<'info, TokenAccount>, &Account<'info, TokenAccount>),
+        amount_in: u64,
+        min_amount_out: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<SwapAmounts> {
+        let (owner_token_account, keeper_token_account) = recipient_accounts;
+
+        self.transfer_from_escrow(escrow, token_one_accounts.1, amount_in, token_program)?;
+
+        pool.settle_swap(
+            dex_configuration_account,
+            token_one_accounts,
+            pool_token_account_two,
+            amount_in,
+            min_amount_out,
+            owner_token_account,
+            Some(keeper_token_account),
+            token_program,
+        )
+    }
+}
@@ -0,0 +1,211 @@
+use crate::errors::DexProgramError;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use std::cmp;
+
+// Vote weights of the current and the previous epoch. Each epoch reuses the slot
+// of the epoch two before it, so an epoch's tally has to be distributed before
+// the next epoch ends.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct EpochWeights {
+    pub epochs: [u64; 2],
+    pub weights: [u128; 2],
+}
+
+impl EpochWeights {
+    // Epochs (8 * 2) + weights (16 * 2)
+    pub const SIZE: usize = 8 * 2 + 16 * 2;
+
+    pub fn weight_at(&self, epoch: u64) -> u128 {
+        let slot = (epoch % 2) as usize;
+
+        if self.epochs[slot] == epoch {
+            self.weights[slot]
+        } else {
+            0_u128
+        }
+    }
+
+    pub fn add(&mut self, epoch: u64, weight: u128) -> Result<()> {
+        let slot = (epoch % 2) as usize;
+
+        self.weights[slot] = self
+            .weight_at(epoch)
+            .checked_add(weight)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.epochs[slot] = epoch;
+
+        Ok(())
+    }
+
+    pub fn sub(&mut self, epoch: u64, weight: u128) -> Result<()> {
+        let slot = (epoch % 2) as usize;
+
+        self.weights[slot] = self
+            .weight_at(epoch)
+            .checked_sub(weight)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.epochs[slot] = epoch;
+
+        Ok(())
+    }
+}
+
+#[account]
+pub struct GaugeController {
+    pub governance_mint: Pubkey,
+    pub emission_mint: Pubkey,
+    pub genesis: i64,
+    pub epoch_duration: i64,
+    pub emissions_per_epoch: u64,
+    pub total_weights: EpochWeights,
+    pub bump: u8,
+    pub reserved: [u8; 64],
+}
+
+impl GaugeController {
+    pub const SEED: &'static str = "GaugeController";
+
+    pub const LOCKER_VAULT_SEED: &'static str = "locker_vault";
+
+    pub const EMISSION_VAULT_SEED: &'static str = "emission_vault";
+
+    // Governance tokens can be locked for at most four years
+    pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+
+    // Discriminator (8) + governance mint (32) + emission mint (32) + genesis (8)
+    // + epoch duration (8) + emissions per epoch (8) + total weights (48) + bump (1)
+    // + reserved (64)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + EpochWeights::SIZE + 1 + 64;
+
+    pub fn new(
+        governance_mint: Pubkey,
+        emission_mint: Pubkey,
+        genesis: i64,
+        epoch_duration: i64,
+        emissions_per_epoch: u64,
+        bump: u8,
+    ) -> Self {
+        Self {
+            governance_mint,
+            emission_mint,
+            genesis,
+            epoch_duration,
+            emissions_per_epoch,
+            total_weights: EpochWeights::default(),
+            bump,
+            reserved: [0_u8; 64],
+        }
+    }
+
+    pub fn epoch_at(&self, now: i64) -> u64 {
+        (now.saturating_sub(self.genesis).max(0) / self.epoch_duration) as u64
+    }
+}
+
+pub trait GaugeControllerAccount<'info> {
+    fn transfer_from_controller(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+}
+
+impl<'info> GaugeControllerAccount<'info> for Account<'info, GaugeController> {
+    fn transfer_from_controller(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.to_account_info(),
+                },
+                &[&[GaugeController::SEED.as_bytes(), &[self.bump]]],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Gauge {
+    pub pool: Pubkey,
+    pub weights: EpochWeights,
+    pub next_distribution_epoch: u64,
+    pub bump: u8,
+    pub reserved: [u8; 32],
+}
+
+impl Gauge {
+    pub const SEED_PREFIX: &'static str = "gauge";
+
+    // Discriminator (8) + pool (32) + weights (48) + next distribution epoch (8)
+    // + bump (1) + reserved (32)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + EpochWeights::SIZE + 8 + 1 + 32;
+
+    pub fn new(pool: Pubkey, next_distribution_epoch: u64, bump: u8) -> Self {
+        Self {
+            pool,
+            weights: EpochWeights::default(),
+            next_distribution_epoch,
+            bump,
+            reserved: [0_u8; 32],
+        }
+    }
+}
+
+#[account]
+pub struct VoteEscrow {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub lock_end: i64,
+    pub vote_epoch: u64,
+    pub used_vote_bps: u16,
+    pub bump: u8,
+    pub reserved: [u8; 32],
+}
+
+impl VoteEscrow {
+    pub const SEED_PREFIX: &'static str = "vote_escrow";
+
+    // Discriminator (8) + owner (32) + amount (8) + lock end (8) + vote epoch (8)
+    // + used vote bps (2) + bump (1) + reserved (32)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 8 + 8 + 8 + 2 + 1 + 32;
+
+    // Voting power decays linearly from the full locked amount at the maximum lock
+    // duration to zero once the lock expires.
+    pub fn voting_power(&self, now: i64) -> u64 {
+        let remaining = cmp::min(
+            self.lock_end.saturating_sub(now).max(0),
+            GaugeController::MAX_LOCK_DURATION,
+        );
+
+        ((self.amount as u128) * (remaining as u128) / (GaugeController::MAX_LOCK_DURATION as u128))
+            as u64
+    }
+}
+
+#[account]
+pub struct GaugeVote {
+    pub epoch: u64,
+    pub weight: u128,
+    pub vote_bps: u16,
+}
+
+impl GaugeVote {
+    pub const SEED_PREFIX: &'static str = "gauge_vote";
+
+    // Discriminator (8) + epoch (8) + weight (16) + vote bps (2)
+    pub const ACCOUNT_SIZE: usize = 8 + 8 + 16 + 2;
+}
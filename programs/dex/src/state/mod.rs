@@ -0,0 +1,1800 @@
+use crate::errors::DexProgramError;
+use crate::helpers::convert_from_float;
+use crate::helpers::convert_to_float;
+use crate::helpers::{transfer_from_user, wrap_sol_from_user};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount};
+use std::cmp;
+use std::ops::Div;
+use std::ops::Mul;
+use std::ops::Sub;
+
+pub mod dca;
+pub mod escrow;
+pub mod gauge;
+pub mod oracle;
+pub mod order;
+pub mod referral;
+pub mod rfq;
+pub mod staking;
+pub mod trigger_order;
+pub mod twamm;
+pub mod vault;
+pub use dca::*;
+pub use escrow::*;
+pub use gauge::*;
+pub use oracle::*;
+pub use order::*;
+pub use referral::*;
+pub use rfq::*;
+pub use staking::*;
+pub use trigger_order::*;
+pub use twamm::*;
+pub use vault::*;
+
+#[cfg(test)]
+mod tests;
+
+// Bits of the `paused` bitfield on the dex configuration and on each pool
+pub const PAUSE_SWAPS: u8 = 1 << 0;
+pub const PAUSE_DEPOSITS: u8 = 1 << 1;
+pub const PAUSE_WITHDRAWALS: u8 = 1 << 2;
+pub const PAUSE_ALL: u8 = PAUSE_SWAPS | PAUSE_DEPOSITS | PAUSE_WITHDRAWALS;
+
+#[account]
+pub struct DexConfiguration {
+    pub fees: f64,
+    pub admin: Pubkey,
+    pub guardian: Pubkey,
+    pub paused: u8,
+    pub version: u8,
+    pub max_referral_fee_bps: u16,
+    pub fee_discount_tiers: [FeeDiscountTier; DexConfiguration::MAX_FEE_DISCOUNT_TIERS],
+    // Tail padding, see `realloc_account`
+    pub reserved: [u8; 22],
+}
+
+impl DexConfiguration {
+    pub const SEED: &'static str = "DexConfiguration";
+
+    pub const VERSION: u8 = 3;
+
+    pub const MAX_FEE_DISCOUNT_TIERS: usize = 4;
+
+    // Discriminator (8) + Pubkey (32) + f64 (8) + guardian (32) + paused (1)
+    // + version (1) + max referral fee (2) + fee discount tiers (10 * 4) + reserved (22)
+    pub const ACCOUNT_SIZE: usize =
+        8 + 32 + 8 + 32 + 1 + 1 + 2 + FeeDiscountTier::SIZE * Self::MAX_FEE_DISCOUNT_TIERS + 22;
+
+    pub fn new(fees: f64, admin: Pubkey) -> Self {
+        Self {
+            fees,
+            admin,
+            guardian: admin,
+            paused: 0_u8,
+            version: Self::VERSION,
+            max_referral_fee_bps: 0_u16,
+            fee_discount_tiers: [FeeDiscountTier::default(); Self::MAX_FEE_DISCOUNT_TIERS],
+            reserved: [0_u8; 22],
+        }
+    }
+
+    // The guardian is a hot key for emergencies: it may halt swaps and deposits
+    // but only the admin can halt withdrawals or resume any operation.
+    pub fn can_pause(&self, authority: Pubkey, flags: u8) -> bool {
+        authority.eq(&self.admin)
+            || (authority.eq(&self.guardian) && flags & PAUSE_WITHDRAWALS == 0)
+    }
+
+    // Referral fee carved out of a swap's input, capped by the configured maximum
+    pub fn referral_fee(&self, amount: u64, referral_fee_bps: u16) -> Result<u64> {
+        if referral_fee_bps > self.max_referral_fee_bps {
+            return err!(DexProgramError::ReferralFeeTooHigh);
+        }
+
+        let referral_fee = (amount as u128)
+            .checked_mul(referral_fee_bps as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            / 10_000_u128;
+
+        Ok(referral_fee as u64)
+    }
+
+    // Discount of the highest tier the staked amount qualifies for
+    pub fn fee_discount_bps(&self, staked_amount: u64) -> u16 {
+        self.fee_discount_tiers
+            .iter()
+            .filter(|tier| tier.min_stake > 0 && staked_amount >= tier.min_stake)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0_u16)
+    }
+}
+
+#[account]
+pub struct LiquidityProvider {
+    pub shares: u64,
+    pub payer: Pubkey,
+    pub version: u8,
+    pub delegate: Pubkey,
+    pub delegated_shares: u64,
+    pub locked_shares: u64,
+    pub lock_until: i64,
+    pub fee_growth_checkpoint_one: u128,
+    pub fee_growth_checkpoint_two: u128,
+    pub fees_owed_one: u64,
+    pub fees_owed_two: u64,
+    pub reward_checkpoints: [u128; LiquidityPool::MAX_REWARDS],
+    pub rewards_owed: [u64; LiquidityPool::MAX_REWARDS],
+    // Tail padding, see `realloc_account`
+    pub reserved: [u8; 16],
+}
+
+impl LiquidityProvider {
+    pub const SEED_PREFIX: &'static str = "LiqudityProvider";
+
+    pub const VERSION: u8 = 5;
+
+    // Discriminator (8) + f64 (8) + payer (32) + version (1) + delegate (32)
+    // + delegated shares (8) + locked shares (8) + lock until (8)
+    // + fee growth checkpoints (16 + 16) + fees owed (8 + 8)
+    // + reward checkpoints (16 * 3) + rewards owed (8 * 3) + reserved (16)
+    pub const ACCOUNT_SIZE: usize = 8
+        + 8
+        + 32
+        + 1
+        + 32
+        + 8
+        + 8
+        + 8
+        + (16 + 16 + 8 + 8)
+        + (16 + 8) * LiquidityPool::MAX_REWARDS
+        + 16;
+
+    pub fn locked_shares_at(&self, now: i64) -> u64 {
+        if now < self.lock_until {
+            self.locked_shares
+        } else {
+            0_u64
+        }
+    }
+
+    pub fn unlocked_shares(&self, now: i64) -> u64 {
+        self.shares.saturating_sub(self.locked_shares_at(now))
+    }
+
+    pub fn is_owner_or_delegate(&self, owner: Pubkey, authority: Pubkey) -> bool {
+        authority.eq(&owner) || (self.delegate != Pubkey::default() && authority.eq(&self.delegate))
+    }
+
+    // Shares burnt by the delegate count against its allowance. Only the owner
+    // raises it again, shares the delegate adds to the position do not.
+    pub fn spend_delegated_shares(&mut self, shares: u64) -> Result<()> {
+        self.delegated_shares = self
+            .delegated_shares
+            .checked_sub(shares)
+            .ok_or(DexProgramError::DelegatedSharesExceeded)?;
+
+        Ok(())
+    }
+}
+
+#[account]
+pub struct LiquidityPool {
+    pub token_one: Pubkey,
+    pub token_two: Pubkey,
+    pub total_supply: u64,
+    pub reserve_one: u64,
+    pub reserve_two: u64,
+    pub bump: u8,
+    pub dynamic_fee_enabled: bool,
+    pub min_fee: f64,
+    pub max_fee: f64,
+    pub volatility_accumulator: f64,
+    pub last_volatility_update: i64,
+    pub paused: u8,
+    pub reference_price: f64,
+    pub reference_slot: u64,
+    pub window_reference_price: f64,
+    pub window_start_slot: u64,
+    pub price_window_slots: u64,
+    pub max_slot_price_movement_bps: u16,
+    pub max_window_price_movement_bps: u16,
+    pub max_trade_size_bps: u16,
+    pub max_price_impact_bps: u16,
+    pub payer: Pubkey,
+    pub version: u8,
+    pub fee_growth_global_one: u128,
+    pub fee_growth_global_two: u128,
+    pub fee_reserve_one: u64,
+    pub fee_reserve_two: u64,
+    pub fees_owed_one: u64,
+    pub fees_owed_two: u64,
+    pub rewards: [RewardVault; LiquidityPool::MAX_REWARDS],
+    pub twamm: Twamm,
+    pub oracle: PriceOracle,
+    // Rewards accrued to liquidity providers and not claimed yet, per reward slot
+    pub rewards_owed: [u64; LiquidityPool::MAX_REWARDS],
+    // Tail padding, see `realloc_account`
+    pub reserved: [u8; 40],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct RewardVault {
+    pub mint: Pubkey,
+    pub emission_rate: u64,
+    pub end_time: i64,
+    pub last_update: i64,
+    pub reward_per_share: u128,
+    pub undistributed: u64,
+}
+
+impl RewardVault {
+    pub const SEED_PREFIX: &'static str = "reward_vault";
+
+    // Mint (32) + emission rate (8) + end time (8) + last update (8)
+    // + reward per share (16) + undistributed (8)
+    pub const SIZE: usize = 32 + 8 + 8 + 8 + 16 + 8;
+
+    pub fn is_initialized(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    pub fn update(&mut self, total_supply: u64, now: i64) -> Result<()> {
+        let emission_end = cmp::min(now, self.end_time);
+
+        if emission_end > self.last_update && total_supply > 0 {
+            // Emissions are capped by what has been funded so a vault never owes
+            // more than it holds.
+            let emitted = cmp::min(
+                (self.emission_rate as u128)
+                    .checked_mul((emission_end - self.last_update) as u128)
+                    .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?,
+                self.undistributed as u128,
+            );
+
+            let reward_growth = (emitted << LiquidityPool::FEE_GROWTH_SHIFT)
+                .checked_div(total_supply as u128)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+            self.reward_per_share = self.reward_per_share.wrapping_add(reward_growth);
+            self.undistributed -= emitted as u64;
+        }
+
+        // Time without any shares outstanding emits nothing, the rewards stay
+        // undistributed for later periods.
+        self.last_update = cmp::max(self.last_update, now);
+
+        Ok(())
+    }
+
+    // Adds rewards on top of the running emissions. What is still scheduled keeps
+    // streaming and the combined amount is spread until the later of the current
+    // end time and `now + duration`. Rewards must be updated up to `now` first.
+    pub fn add_emissions(&mut self, amount: u64, now: i64, duration: i64) -> Result<()> {
+        let scheduled = if self.end_time > now {
+            (self.emission_rate as u128)
+                .checked_mul((self.end_time - now) as u128)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+        } else {
+            0_u128
+        };
+
+        let end_time = cmp::max(self.end_time, now.saturating_add(duration));
+
+        self.emission_rate = (scheduled
+            .checked_add(amount as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            / (end_time - now) as u128) as u64;
+        self.end_time = end_time;
+        self.undistributed = self
+            .undistributed
+            .checked_add(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+}
+
+impl LiquidityPool {
+    pub const POOL_SEED_PREFIX: &'static str = "liquidity_pool";
+
+    // Temporary wrapped SOL account a native SOL leg of a user's trade settles through
+    pub const NATIVE_SOL_SEED_PREFIX: &'static str = "native_sol";
+
+    pub const VERSION: u8 = 5;
+
+    // Number of reward tokens that can be emitted to the liquidity providers at once
+    pub const MAX_REWARDS: usize = 3;
+
+    // Discriminator (8)
+    // + Pubkey (32) + Pubkey (32) + totalsupply (8) + reserve one (8) + reserve two (8) + Bump (1)
+    // + dynamic fee: enabled (1) + min fee (8) + max fee (8) + volatility accumulator (8)
+    //   + last volatility update (8)
+    // + paused (1)
+    // + circuit breaker: reference price (8) + reference slot (8) + window reference price (8)
+    //   + window start slot (8) + price window slots (8) + max slot movement (2)
+    //   + max window movement (2)
+    // + trade limits: max trade size (2) + max price impact (2)
+    // + payer (32) + version (1)
+    // + fees: fee growth global one (16) + fee growth global two (16) + fee reserve one (8)
+    //   + fee reserve two (8) + fees owed one (8) + fees owed two (8)
+    // + rewards (80 * 3)
+    // + twamm (1992)
+    // + oracle (532)
+    // + rewards owed (8 * 3) + reserved (40)
+    pub const ACCOUNT_SIZE: usize = 8
+        + (32 + 32 + 8 + 8 + 8 + 1)
+        + (1 + 8 + 8 + 8 + 8)
+        + 1
+        + (8 + 8 + 8 + 8 + 8 + 2 + 2)
+        + (2 + 2)
+        + 32
+        + 1
+        + (16 + 16 + 8 + 8 + 8 + 8)
+        + RewardVault::SIZE * Self::MAX_REWARDS
+        + Twamm::SIZE
+        + PriceOracle::SIZE
+        + 8 * Self::MAX_REWARDS
+        + 40;
+
+    // Seconds after which the volatility accumulator decays to half its value
+    pub const VOLATILITY_HALF_LIFE: i64 = 600;
+
+    // Accumulated relative price movement at which the dynamic fee reaches max fee
+    pub const VOLATILITY_SATURATION: f64 = 0.1;
+
+    // Fee growth is tracked per share as a Q64.64 fixed point number
+    pub const FEE_GROWTH_SHIFT: u32 = 64;
+
+    pub fn generate_seed(token_one: Pubkey, token_two: Pubkey) -> String {
+        if token_one > token_two {
+            format!("{}{}", token_one, token_two)
+        } else {
+            format!("{}{}", token_two, token_one)
+        }
+    }
+
+    pub fn new(token_one: Pubkey, token_two: Pubkey, bump: u8, payer: Pubkey) -> Self {
+        Self {
+            token_one,
+            token_two,
+            total_supply: 0_u64,
+            reserve_one: 0_u64,
+            reserve_two: 0_u64,
+            bump,
+            dynamic_fee_enabled: false,
+            min_fee: 0_f64,
+            max_fee: 0_f64,
+            volatility_accumulator: 0_f64,
+            last_volatility_update: 0_i64,
+            paused: 0_u8,
+            reference_price: 0_f64,
+            reference_slot: 0_u64,
+            window_reference_price: 0_f64,
+            window_start_slot: 0_u64,
+            price_window_slots: 0_u64,
+            max_slot_price_movement_bps: 0_u16,
+            max_window_price_movement_bps: 0_u16,
+            max_trade_size_bps: 0_u16,
+            max_price_impact_bps: 0_u16,
+            payer,
+            version: Self::VERSION,
+            fee_growth_global_one: 0_u128,
+            fee_growth_global_two: 0_u128,
+            fee_reserve_one: 0_u64,
+            fee_reserve_two: 0_u64,
+            fees_owed_one: 0_u64,
+            fees_owed_two: 0_u64,
+            rewards: [RewardVault::default(); Self::MAX_REWARDS],
+            twamm: Twamm::default(),
+            oracle: PriceOracle::default(),
+            rewards_owed: [0_u64; Self::MAX_REWARDS],
+            reserved: [0_u8; 40],
+        }
+    }
+
+    pub fn is_paused(&self, dex_configuration: &DexConfiguration, operation: u8) -> bool {
+        (dex_configuration.paused | self.paused) & operation != 0
+    }
+
+    pub fn price_movement_bps(reference_price: f64, price: f64) -> f64 {
+        if reference_price <= 0_f64 {
+            return 0_f64;
+        }
+
+        (price - reference_price)
+            .abs()
+            .div(reference_price)
+            .mul(10_000_f64)
+    }
+
+    pub fn is_token_one(&self, mint: Pubkey) -> bool {
+        mint.eq(&self.token_one)
+    }
+
+    pub fn has_native_mint(&self) -> bool {
+        self.token_one.eq(&native_mint::ID) || self.token_two.eq(&native_mint::ID)
+    }
+
+    pub fn spot_price(reserve_one: u64, reserve_two: u64) -> f64 {
+        if reserve_one == 0 {
+            return 0_f64;
+        }
+
+        (reserve_two as f64).div(reserve_one as f64)
+    }
+
+    pub fn decayed_volatility(&self, now: i64) -> f64 {
+        let elapsed = now.saturating_sub(self.last_volatility_update).max(0);
+        let half_lives = (elapsed as f64).div(Self::VOLATILITY_HALF_LIFE as f64);
+
+        self.volatility_accumulator.mul(0.5_f64.powf(half_lives))
+    }
+
+    pub fn current_fee(&self, dex_configuration: &DexConfiguration, now: i64) -> f64 {
+        if !self.dynamic_fee_enabled {
+            return dex_configuration.fees;
+        }
+
+        let utilization = self
+            .decayed_volatility(now)
+            .div(Self::VOLATILITY_SATURATION)
+            .min(1_f64);
+
+        self.min_fee + (self.max_fee - self.min_fee).mul(utilization)
+    }
+
+    // Fee charged to a trader with the given staking discount
+    pub fn discounted_fee(
+        &self,
+        dex_configuration: &DexConfiguration,
+        now: i64,
+        fee_discount_bps: u16,
+    ) -> f64 {
+        self.current_fee(dex_configuration, now)
+            .mul(10_000_f64 - fee_discount_bps as f64)
+            .div(10_000_f64)
+    }
+
+    pub fn quote_swap_exact_in(
+        &self,
+        fees: f64,
+        is_token_one_in: bool,
+        decimals_in: u8,
+        amount_in: u64,
+    ) -> Result<SwapAmounts> {
+        if amount_in == 0 {
+            return err!(DexProgramError::InvalidAmount);
+        }
+
+        // xy = k => Constant product formula
+        // (x + dx)(y - dy) = k
+        // y - dy = k / (x + dx)
+        // y - dy = xy / (x + dx)
+        // dy = y - (xy / (x + dx))
+        // dy = yx + ydx - xy / (x + dx)
+        // formula => dy = ydx / (x + dx)
+
+        let adjusted_amount_in_float = convert_to_float(amount_in, decimals_in)
+            .div(100_f64)
+            .mul(100_f64.sub(fees));
+
+        let adjusted_amount = convert_from_float(adjusted_amount_in_float, decimals_in);
+
+        let (reserve_in, reserve_out) = if is_token_one_in {
+            (self.reserve_one, self.reserve_two)
+        } else {
+            (self.reserve_two, self.reserve_one)
+        };
+
+        let denominator_sum = (reserve_in as u128)
+            .checked_add(adjusted_amount as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let numerator_mul = (reserve_out as u128)
+            .checked_mul(adjusted_amount as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let amount_out = numerator_mul
+            .checked_div(denominator_sum)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)? as u64;
+
+        Ok(SwapAmounts {
+            amount_in,
+            amount_out,
+            fee_amount: amount_in.saturating_sub(adjusted_amount),
+            referral_fee: 0_u64,
+        })
+    }
+
+    pub fn check_trade_limits(
+        &self,
+        is_token_one_in: bool,
+        swap_amounts: &SwapAmounts,
+    ) -> Result<()> {
+        let (reserve_in, reserve_out) = if is_token_one_in {
+            (self.reserve_one, self.reserve_two)
+        } else {
+            (self.reserve_two, self.reserve_one)
+        };
+
+        if self.max_trade_size_bps > 0 {
+            let max_trade_size = (reserve_in as u128)
+                .checked_mul(self.max_trade_size_bps as u128)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+                / 10_000_u128;
+
+            if swap_amounts.amount_in as u128 > max_trade_size {
+                return err!(DexProgramError::TradeSizeTooLarge);
+            }
+        }
+
+        if self.max_price_impact_bps > 0 {
+            let new_reserve_in = reserve_in.saturating_add(swap_amounts.amount_in);
+            let new_reserve_out = reserve_out.saturating_sub(swap_amounts.amount_out);
+
+            let price_impact_bps = LiquidityPool::price_movement_bps(
+                LiquidityPool::spot_price(reserve_in, reserve_out),
+                LiquidityPool::spot_price(new_reserve_in, new_reserve_out),
+            );
+
+            if price_impact_bps > self.max_price_impact_bps as f64 {
+                return err!(DexProgramError::PriceImpactTooHigh);
+            }
+        }
+
+        Ok(())
+    }
+
+    // Reserves once the swap has traded against the curve. The fee is held in the
+    // vault next to the reserves until the liquidity providers claim it, so only
+    // the remainder of the input is added.
+    pub fn reserves_after_swap(
+        &self,
+        is_token_one_in: bool,
+        swap_amounts: &SwapAmounts,
+    ) -> Result<(u64, u64)> {
+        let (reserve_in, reserve_out) = if is_token_one_in {
+            (self.reserve_one, self.reserve_two)
+        } else {
+            (self.reserve_two, self.reserve_one)
+        };
+
+        let new_reserve_in = reserve_in
+            .checked_add(
+                swap_amounts
+                    .amount_in
+                    .saturating_sub(swap_amounts.fee_amount),
+            )
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let new_reserve_out = reserve_out
+            .checked_sub(swap_amounts.amount_out)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        if is_token_one_in {
+            Ok((new_reserve_in, new_reserve_out))
+        } else {
+            Ok((new_reserve_out, new_reserve_in))
+        }
+    }
+
+    pub fn check_circuit_breaker(
+        &mut self,
+        price_before: f64,
+        price_after: f64,
+        slot: u64,
+    ) -> Result<()> {
+        // The first swap of a slot or of a window records the price it started from
+        if slot != self.reference_slot {
+            self.reference_price = price_before;
+            self.reference_slot = slot;
+        }
+
+        if slot
+            >= self
+                .window_start_slot
+                .saturating_add(self.price_window_slots)
+        {
+            self.window_reference_price = price_before;
+            self.window_start_slot = slot;
+        }
+
+        if self.max_slot_price_movement_bps > 0
+            && LiquidityPool::price_movement_bps(self.reference_price, price_after)
+                > self.max_slot_price_movement_bps as f64
+        {
+            return err!(DexProgramError::PriceImpactTooHigh);
+        }
+
+        if self.max_window_price_movement_bps > 0
+            && LiquidityPool::price_movement_bps(self.window_reference_price, price_after)
+                > self.max_window_price_movement_bps as f64
+        {
+            return err!(DexProgramError::PriceImpactTooHigh);
+        }
+
+        Ok(())
+    }
+
+    pub fn quote_swap_exact_out(
+        &self,
+        fees: f64,
+        is_token_one_in: bool,
+        decimals_in: u8,
+        amount_out: u64,
+    ) -> Result<SwapAmounts> {
+        let (reserve_in, reserve_out) = if is_token_one_in {
+            (self.reserve_one, self.reserve_two)
+        } else {
+            (self.reserve_two, self.reserve_one)
+        };
+
+        if amount_out == 0 || amount_out >= reserve_out || fees >= 100_f64 {
+            return err!(DexProgramError::InvalidAmount);
+        }
+
+        // Inverse of the exact in formula => dx = ceil(x * dy / (y - dy))
+        let numerator_mul = (reserve_in as u128)
+            .checked_mul(amount_out as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let denominator_sub = (reserve_out - amount_out) as u128;
+
+        let adjusted_amount = numerator_mul
+            .checked_add(denominator_sub - 1)
+            .and_then(|value| value.checked_div(denominator_sub))
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let mut amount_in = (adjusted_amount as f64)
+            .mul(100_f64)
+            .div(100_f64.sub(fees))
+            .ceil() as u64;
+
+        // The fee adjustment rounds through floats, so settle on the smallest
+        // input for which the exact in path yields at least the requested output.
+        let mut quote = self.quote_swap_exact_in(fees, is_token_one_in, decimals_in, amount_in)?;
+
+        while quote.amount_out < amount_out {
+            amount_in = amount_in
+                .checked_add(1)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            quote = self.quote_swap_exact_in(fees, is_token_one_in, decimals_in, amount_in)?;
+        }
+
+        while amount_in > 1 {
+            let lower_quote =
+                self.quote_swap_exact_in(fees, is_token_one_in, decimals_in, amount_in - 1)?;
+
+            if lower_quote.amount_out < amount_out {
+                break;
+            }
+
+            amount_in -= 1;
+            quote = lower_quote;
+        }
+
+        Ok(quote)
+    }
+
+    pub fn quote_add_liquidity(
+        &self,
+        amount_one: u64,
+        amount_two: u64,
+        decimals_one: u8,
+        decimals_two: u8,
+    ) -> Result<u64> {
+        let shares_to_allocate = if self.total_supply == 0 {
+            let sqrt_shares = (convert_to_float(amount_one, decimals_one)
+                .mul(convert_to_float(amount_two, decimals_two)))
+            .sqrt();
+
+            sqrt_shares as u64
+        } else {
+            let mul_value = amount_one
+                .checked_mul(self.total_supply)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            let shares_one = mul_value
+                .checked_div(self.reserve_one)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+            let mul_value = amount_two
+                .checked_mul(self.total_supply)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            let shares_two = mul_value
+                .checked_div(self.reserve_two)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+            cmp::min(shares_one, shares_two)
+        };
+
+        if shares_to_allocate == 0 {
+            return err!(DexProgramError::FailedToAddLiquidity);
+        }
+
+        Ok(shares_to_allocate)
+    }
+
+    // Amount of the token held in excess that has to be swapped so that what is
+    // left of both amounts can be added at the pool ratio. Returns whether the
+    // excess is token one along with the amount to swap.
+    pub fn balancing_swap_amount(
+        &self,
+        fees: f64,
+        amount_one: u64,
+        amount_two: u64,
+    ) -> (bool, u64) {
+        if self.reserve_one == 0 || self.reserve_two == 0 {
+            return (true, 0_u64);
+        }
+
+        let (amount_one, amount_two) = (amount_one as f64, amount_two as f64);
+        let (reserve_one, reserve_two) = (self.reserve_one as f64, self.reserve_two as f64);
+
+        let balanced_one = amount_two.mul(reserve_one).div(reserve_two);
+
+        let (is_token_one_in, excess, reserve_in) = if amount_one >= balanced_one {
+            (
+                true,
+                amount_one.sub(balanced_one),
+                reserve_one + balanced_one,
+            )
+        } else {
+            let balanced_two = amount_one.mul(reserve_two).div(reserve_one);
+            (
+                false,
+                amount_two.sub(balanced_two),
+                reserve_two + balanced_two,
+            )
+        };
+
+        // Swapping s of the excess e leaves (e - s) / dy at the post swap ratio
+        // (x + s) / (y - dy), with dy = (1 - f) s y / (x + (1 - f) s) this solves to
+        // s = (sqrt(x^2 (2 - f)^2 + 4 (1 - f) x e) - x (2 - f)) / 2 (1 - f)
+        let fee = fees.div(100_f64);
+        let swap_amount = ((reserve_in.mul(2_f64 - fee)).powi(2)
+            + 4_f64.mul(1_f64 - fee).mul(reserve_in).mul(excess))
+        .sqrt()
+        .sub(reserve_in.mul(2_f64 - fee))
+        .div(2_f64.mul(1_f64 - fee));
+
+        (is_token_one_in, swap_amount.max(0_f64) as u64)
+    }
+
+    pub fn quote_remove_liquidity(&self, shares: u64) -> Result<(u64, u64)> {
+        if shares == 0 || shares > self.total_supply {
+            return err!(DexProgramError::FailedToRemoveLiquidity);
+        }
+
+        let mul_value = shares
+            .checked_mul(self.reserve_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let amount_out_one = mul_value
+            .checked_div(self.total_supply)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let mul_value = shares
+            .checked_mul(self.reserve_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let amount_out_two = mul_value
+            .checked_div(self.total_supply)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        if amount_out_one == 0 || amount_out_two == 0 {
+            return err!(DexProgramError::FailedToRemoveLiquidity);
+        }
+
+        Ok((amount_out_one, amount_out_two))
+    }
+
+    // Everything the vaults have to hold: the reserves, unclaimed fees and the
+    // balances of long-term orders.
+    pub fn committed_balances(&self) -> Result<(u64, u64)> {
+        let committed_one = self
+            .reserve_one
+            .checked_add(self.fee_reserve_one)
+            .and_then(|amount| amount.checked_add(self.twamm.balances[0]))
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let committed_two = self
+            .reserve_two
+            .checked_add(self.fee_reserve_two)
+            .and_then(|amount| amount.checked_add(self.twamm.balances[1]))
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok((committed_one, committed_two))
+    }
+
+    pub fn collect_fee(&mut self, is_token_one: bool, fee_amount: u64) -> Result<()> {
+        if fee_amount == 0 || self.total_supply == 0 {
+            return Ok(());
+        }
+
+        let fee_growth = ((fee_amount as u128) << Self::FEE_GROWTH_SHIFT)
+            .checked_div(self.total_supply as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        // Fee growth only ever increases and is compared by difference, so it is
+        // allowed to wrap around like a counter.
+        if is_token_one {
+            self.fee_growth_global_one = self.fee_growth_global_one.wrapping_add(fee_growth);
+            self.fee_reserve_one = self
+                .fee_reserve_one
+                .checked_add(fee_amount)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        } else {
+            self.fee_growth_global_two = self.fee_growth_global_two.wrapping_add(fee_growth);
+            self.fee_reserve_two = self
+                .fee_reserve_two
+                .checked_add(fee_amount)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn update_rewards(&mut self, now: i64) -> Result<()> {
+        let total_supply = self.total_supply;
+
+        for reward_vault in self.rewards.iter_mut() {
+            if reward_vault.is_initialized() {
+                reward_vault.update(total_supply, now)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn reward_index(&self, reward_index: u8, reward_mint: Pubkey) -> Result<usize> {
+        let index = reward_index as usize;
+
+        match self.rewards.get(index) {
+            Some(reward_vault)
+                if reward_vault.is_initialized() && reward_vault.mint == reward_mint =>
+            {
+                Ok(index)
+            }
+            _ => err!(DexProgramError::InvalidRewardVault),
+        }
+    }
+
+    // Whether any reward slot pays out in a token other than the pool tokens
+    pub fn has_foreign_rewards(&self) -> bool {
+        self.rewards.iter().any(|reward_vault| {
+            reward_vault.is_initialized()
+                && reward_vault.mint != self.token_one
+                && reward_vault.mint != self.token_two
+        })
+    }
+
+    pub fn has_reward_vaults(&self) -> bool {
+        self.rewards
+            .iter()
+            .any(|reward_vault| reward_vault.is_initialized())
+    }
+
+    pub fn accrue_rewards(&mut self, liquidity_provider: &mut LiquidityProvider) -> Result<()> {
+        for (index, reward_vault) in self.rewards.iter().enumerate() {
+            let accrued = (liquidity_provider.shares as u128)
+                .checked_mul(
+                    reward_vault
+                        .reward_per_share
+                        .wrapping_sub(liquidity_provider.reward_checkpoints[index]),
+                )
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+                >> Self::FEE_GROWTH_SHIFT;
+
+            liquidity_provider.rewards_owed[index] = liquidity_provider.rewards_owed[index]
+                .checked_add(accrued as u64)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+            liquidity_provider.reward_checkpoints[index] = reward_vault.reward_per_share;
+
+            self.rewards_owed[index] = self.rewards_owed[index]
+                .checked_add(accrued as u64)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        }
+
+        Ok(())
+    }
+
+    // Rewards paid out of a reward vault are no longer owed by the pool
+    pub fn release_rewards(&mut self, index: usize, amount: u64) -> Result<()> {
+        self.rewards_owed[index] = self.rewards_owed[index]
+            .checked_sub(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    pub fn accrue_fees(&mut self, liquidity_provider: &mut LiquidityProvider) -> Result<()> {
+        let accrued_fees = |fee_growth_global: u128, fee_growth_checkpoint: u128| -> Result<u64> {
+            let accrued = (liquidity_provider.shares as u128)
+                .checked_mul(fee_growth_global.wrapping_sub(fee_growth_checkpoint))
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+                >> Self::FEE_GROWTH_SHIFT;
+
+            Ok(accrued as u64)
+        };
+
+        let accrued_one = accrued_fees(
+            self.fee_growth_global_one,
+            liquidity_provider.fee_growth_checkpoint_one,
+        )?;
+        let accrued_two = accrued_fees(
+            self.fee_growth_global_two,
+            liquidity_provider.fee_growth_checkpoint_two,
+        )?;
+
+        liquidity_provider.fees_owed_one = liquidity_provider
+            .fees_owed_one
+            .checked_add(accrued_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        liquidity_provider.fees_owed_two = liquidity_provider
+            .fees_owed_two
+            .checked_add(accrued_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        liquidity_provider.fee_growth_checkpoint_one = self.fee_growth_global_one;
+        liquidity_provider.fee_growth_checkpoint_two = self.fee_growth_global_two;
+
+        self.fees_owed_one = self
+            .fees_owed_one
+            .checked_add(accrued_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.fees_owed_two = self
+            .fees_owed_two
+            .checked_add(accrued_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    // Takes fees that were claimed by a liquidity provider out of the fee reserves.
+    // Whatever is left in the fee reserves once nothing is owed is rounding dust.
+    pub fn release_fees(&mut self, amount_one: u64, amount_two: u64) -> Result<()> {
+        self.fee_reserve_one = self
+            .fee_reserve_one
+            .checked_sub(amount_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.fee_reserve_two = self
+            .fee_reserve_two
+            .checked_sub(amount_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.fees_owed_one = self
+            .fees_owed_one
+            .checked_sub(amount_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.fees_owed_two = self
+            .fees_owed_two
+            .checked_sub(amount_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SwapAmounts {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    // Part of `amount_in` paid to the referrer instead of going into the pool
+    pub referral_fee: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LiquidityAmounts {
+    pub shares: u64,
+    pub amount_one: u64,
+    pub amount_two: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeAmounts {
+    pub amount_one: u64,
+    pub amount_two: u64,
+}
+
+// Temporary wrapped SOL account of each leg of a trade, only a leg paid in native
+// SOL has one
+pub type NativeSolLegs<'a, 'info> = (
+    Option<&'a mut Account<'info, TokenAccount>>,
+    Option<&'a mut Account<'info, TokenAccount>>,
+);
+
+// Hands the temporary wrapped SOL account to the leg of the trade paid in native
+// SOL, the other leg settles through the user's token account.
+pub fn native_sol_legs<'a, 'info>(
+    native_sol: bool,
+    native_sol_account: Option<&'a mut Account<'info, TokenAccount>>,
+    mint_token_one: &Account<'info, Mint>,
+) -> Result<NativeSolLegs<'a, 'info>> {
+    if !native_sol {
+        return Ok((None, None));
+    }
+
+    let Some(native_sol_account) = native_sol_account else {
+        return err!(DexProgramError::MissingTokenAccount);
+    };
+
+    if mint_token_one.key().eq(&native_mint::ID) {
+        Ok((Some(native_sol_account), None))
+    } else {
+        Ok((None, Some(native_sol_account)))
+    }
+}
+
+// Token account a leg settles through: the temporary wrapped SOL account of a
+// native SOL leg, the user's own token account otherwise.
+pub fn settlement_account<'a, 'info>(
+    native_sol_account: Option<&'a mut Account<'info, TokenAccount>>,
+    user_token_account: Option<&'a mut Account<'info, TokenAccount>>,
+) -> Result<&'a mut Account<'info, TokenAccount>> {
+    native_sol_account
+        .or(user_token_account)
+        .ok_or_else(|| error!(DexProgramError::MissingTokenAccount))
+}
+
+pub trait LiquidityPoolAccount<'info> {
+    fn grant_shares(
+        &mut self,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        hares: u64,
+    ) -> Result<()>;
+    fn remove_shares(
+        &mut self,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        shares: u64,
+    ) -> Result<()>;
+    fn transfer_shares(
+        &mut self,
+        from_liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        to_liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        shares: u64,
+    ) -> Result<()>;
+    fn update_reserves(&mut self, reserve_one: u64, reserve_two: u64) -> Result<()>;
+
+    fn update_volatility(&mut self, price_before: f64, price_after: f64, now: i64) -> Result<()>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_liquidity(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        amount_one: u64,
+        amount_two: u64,
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<LiquidityAmounts>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn remove_liquidity(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        shares: u64,
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<LiquidityAmounts>;
+
+    fn claim_fees(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<FeeAmounts>;
+
+    // Moves the reserves and fee accounting of a swap without transferring any
+    // tokens, the caller is responsible for settling the vaults.
+    fn apply_swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        is_token_one_in: bool,
+        decimals_in: u8,
+        amount: u64,
+        fee_discount_bps: u16,
+    ) -> Result<SwapAmounts>;
+
+    // Swaps input that was already moved into the pool vault and pays the output
+    // out of the pool. With a keeper account the keeper is paid its fee out of
+    // the output first, `min_amount_out` applies to what the recipient receives.
+    #[allow(clippy::too_many_arguments)]
+    fn settle_swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        token_one_accounts: (&Account<'info, Mint>, &mut Account<'info, TokenAccount>),
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+        amount_in: u64,
+        min_amount_out: u64,
+        recipient_token_account: &Account<'info, TokenAccount>,
+        keeper_token_account: Option<&Account<'info, TokenAccount>>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<SwapAmounts>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        amount: u64,
+        fee_discount_bps: u16,
+        native_sol: bool,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<SwapAmounts>;
+
+    fn check_vault_balances(
+        &self,
+        pool_token_account_one: &mut Account<'info, TokenAccount>,
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+    ) -> Result<()>;
+
+    fn close_pool_token_account(
+        &self,
+        account: &Account<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    fn transfer_token_from_pool(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    fn transfer_token_to_pool(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    fn transfer_sol_to_pool(
+        &self,
+        from: &Signer<'info>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        system_program: &Program<'info, System>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+
+    #[allow(clippy::too_many_arguments)]
+    fn deposit_to_pool(
+        &self,
+        mint: &Account<'info, Mint>,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        native_sol: bool,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<()>;
+}
+
+impl<'info> LiquidityPoolAccount<'info> for Account<'info, LiquidityPool> {
+    fn grant_shares(
+        &mut self,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        shares: u64,
+    ) -> Result<()> {
+        // Settle fees and rewards earned on the old balance before it changes
+        self.update_rewards(Clock::get()?.unix_timestamp)?;
+        self.accrue_fees(liquidity_provider_account)?;
+        self.accrue_rewards(liquidity_provider_account)?;
+
+        liquidity_provider_account.shares = liquidity_provider_account
+            .shares
+            .checked_add(shares)
+            .ok_or(DexProgramError::FailedToAllocateShares)?;
+
+        self.total_supply = self
+            .total_supply
+            .checked_add(shares)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    fn remove_shares(
+        &mut self,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        shares: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+
+        if shares > liquidity_provider_account.unlocked_shares(now) {
+            return err!(DexProgramError::SharesLocked);
+        }
+
+        self.update_rewards(now)?;
+        self.accrue_fees(liquidity_provider_account)?;
+        self.accrue_rewards(liquidity_provider_account)?;
+
+        liquidity_provider_account.shares = liquidity_provider_account
+            .shares
+            .checked_sub(shares)
+            .ok_or(DexProgramError::FailedToDeallocateShares)?;
+
+        self.total_supply = self
+            .total_supply
+            .checked_sub(shares)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    fn transfer_shares(
+        &mut self,
+        from_liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        to_liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        shares: u64,
+    ) -> Result<()> {
+        if shares == 0 {
+            return err!(DexProgramError::InvalidAmount);
+        }
+
+        if from_liquidity_provider_account.shares < shares {
+            return err!(DexProgramError::InsufficientShares);
+        }
+
+        // Moving shares leaves the total supply and the reserves unchanged
+        self.remove_shares(from_liquidity_provider_account, shares)?;
+        self.grant_shares(to_liquidity_provider_account, shares)?;
+
+        Ok(())
+    }
+
+    fn update_reserves(&mut self, reserve_one: u64, reserve_two: u64) -> Result<()> {
+        self.observe(Clock::get()?.unix_timestamp);
+
+        self.reserve_one = reserve_one;
+        self.reserve_two = reserve_two;
+
+        Ok(())
+    }
+
+    fn update_volatility(&mut self, price_before: f64, price_after: f64, now: i64) -> Result<()> {
+        let movement = if price_before > 0_f64 {
+            (price_after - price_before).abs().div(price_before)
+        } else {
+            0_f64
+        };
+
+        self.volatility_accumulator = self.decayed_volatility(now) + movement;
+        self.last_volatility_update = now;
+
+        Ok(())
+    }
+
+    fn add_liquidity(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        amount_one: u64,
+        amount_two: u64,
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<LiquidityAmounts> {
+        if native_sol && !self.has_native_mint() {
+            return err!(DexProgramError::NativeSolNotSupported);
+        }
+
+        // Amounts arrive in the order the mints were passed, which may be the
+        // reverse of the order the pool tracks its reserves in.
+        let (pool_amount_one, pool_amount_two, decimals_one, decimals_two) =
+            if self.is_token_one(token_one_accounts.0.key()) {
+                (
+                    amount_one,
+                    amount_two,
+                    token_one_accounts.0.decimals,
+                    token_two_accounts.0.decimals,
+                )
+            } else {
+                (
+                    amount_two,
+                    amount_one,
+                    token_two_accounts.0.decimals,
+                    token_one_accounts.0.decimals,
+                )
+            };
+
+        let shares_to_allocate =
+            self.quote_add_liquidity(pool_amount_one, pool_amount_two, decimals_one, decimals_two)?;
+
+        self.grant_shares(liquidity_provider_account, shares_to_allocate)?;
+
+        let new_reserves_one = self
+            .reserve_one
+            .checked_add(pool_amount_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let new_reserves_two = self
+            .reserve_two
+            .checked_add(pool_amount_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        self.update_reserves(new_reserves_one, new_reserves_two)?;
+
+        self.deposit_to_pool(
+            token_one_accounts.0,
+            token_one_accounts.2,
+            token_one_accounts.1,
+            amount_one,
+            native_sol,
+            authority,
+            token_program,
+            system_program,
+        )?;
+
+        self.deposit_to_pool(
+            token_two_accounts.0,
+            token_two_accounts.2,
+            token_two_accounts.1,
+            amount_two,
+            native_sol,
+            authority,
+            token_program,
+            system_program,
+        )?;
+
+        Ok(LiquidityAmounts {
+            shares: shares_to_allocate,
+            amount_one,
+            amount_two,
+        })
+    }
+
+    fn remove_liquidity(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        shares: u64,
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<LiquidityAmounts> {
+        if shares == 0 {
+            return err!(DexProgramError::FailedToRemoveLiquidity);
+        }
+
+        if native_sol && !self.has_native_mint() {
+            return err!(DexProgramError::NativeSolNotSupported);
+        }
+
+        if liquidity_provider_account.shares < shares {
+            return err!(DexProgramError::InsufficientShares);
+        }
+
+        let (pool_amount_out_one, pool_amount_out_two) = self.quote_remove_liquidity(shares)?;
+
+        self.remove_shares(liquidity_provider_account, shares)?;
+
+        let new_reserves_one = self
+            .reserve_one
+            .checked_sub(pool_amount_out_one)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let new_reserves_two = self
+            .reserve_two
+            .checked_sub(pool_amount_out_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        self.update_reserves(new_reserves_one, new_reserves_two)?;
+
+        let (amount_out_one, amount_out_two) = if self.is_token_one(token_one_accounts.0.key()) {
+            (pool_amount_out_one, pool_amount_out_two)
+        } else {
+            (pool_amount_out_two, pool_amount_out_one)
+        };
+
+        self.transfer_token_from_pool(
+            token_one_accounts.1,
+            token_one_accounts.2,
+            amount_out_one,
+            token_program,
+        )?;
+
+        self.transfer_token_from_pool(
+            token_two_accounts.1,
+            token_two_accounts.2,
+            amount_out_two,
+            token_program,
+        )?;
+
+        Ok(LiquidityAmounts {
+            shares,
+            amount_one: amount_out_one,
+            amount_two: amount_out_two,
+        })
+    }
+
+    fn claim_fees(
+        &mut self,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        native_sol: bool,
+        liquidity_provider_account: &mut Account<'info, LiquidityProvider>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<FeeAmounts> {
+        if native_sol && !self.has_native_mint() {
+            return err!(DexProgramError::NativeSolNotSupported);
+        }
+
+        self.accrue_fees(liquidity_provider_account)?;
+
+        let pool_fees_one = liquidity_provider_account.fees_owed_one;
+        let pool_fees_two = liquidity_provider_account.fees_owed_two;
+
+        if pool_fees_one == 0 && pool_fees_two == 0 {
+            return err!(DexProgramError::NoFeesToClaim);
+        }
+
+        liquidity_provider_account.fees_owed_one = 0_u64;
+        liquidity_provider_account.fees_owed_two = 0_u64;
+
+        self.release_fees(pool_fees_one, pool_fees_two)?;
+
+        let (fees_one, fees_two) = if self.is_token_one(token_one_accounts.0.key()) {
+            (pool_fees_one, pool_fees_two)
+        } else {
+            (pool_fees_two, pool_fees_one)
+        };
+
+        self.transfer_token_from_pool(
+            token_one_accounts.1,
+            token_one_accounts.2,
+            fees_one,
+            token_program,
+        )?;
+
+        self.transfer_token_from_pool(
+            token_two_accounts.1,
+            token_two_accounts.2,
+            fees_two,
+            token_program,
+        )?;
+
+        Ok(FeeAmounts {
+            amount_one: fees_one,
+            amount_two: fees_two,
+        })
+    }
+
+    fn apply_swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        is_token_one_in: bool,
+        decimals_in: u8,
+        amount: u64,
+        fee_discount_bps: u16,
+    ) -> Result<SwapAmounts> {
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+
+        self.execute_virtual_orders(dex_configuration_account, now)?;
+
+        let fees = self.discounted_fee(dex_configuration_account, now, fee_discount_bps);
+
+        let swap_amounts = self.quote_swap_exact_in(fees, is_token_one_in, decimals_in, amount)?;
+
+        self.check_trade_limits(is_token_one_in, &swap_amounts)?;
+
+        let (new_reserve_one, new_reserve_two) =
+            self.reserves_after_swap(is_token_one_in, &swap_amounts)?;
+
+        let price_before = LiquidityPool::spot_price(self.reserve_one, self.reserve_two);
+
+        self.update_reserves(new_reserve_one, new_reserve_two)?;
+
+        let price_after = LiquidityPool::spot_price(self.reserve_one, self.reserve_two);
+
+        self.collect_fee(is_token_one_in, swap_amounts.fee_amount)?;
+        self.check_circuit_breaker(price_before, price_after, clock.slot)?;
+        self.update_volatility(price_before, price_after, now)?;
+
+        Ok(swap_amounts)
+    }
+
+    fn swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        token_one_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        token_two_accounts: (
+            &mut Account<'info, Mint>,
+            &mut Account<'info, TokenAccount>,
+            &mut Account<'info, TokenAccount>,
+        ),
+        amount: u64,
+        fee_discount_bps: u16,
+        native_sol: bool,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<SwapAmounts> {
+        if amount == 0 {
+            return err!(DexProgramError::InvalidAmount);
+        }
+
+        if native_sol && !self.has_native_mint() {
+            return err!(DexProgramError::NativeSolNotSupported);
+        }
+
+        // The pool reserves are stored in the order the pool was created with, so
+        // swapping in the second token of the pool flips which reserve is x and y.
+        let is_token_one_in = self.is_token_one(token_one_accounts.0.key());
+
+        let swap_amounts = self.apply_swap(
+            dex_configuration_account,
+            is_token_one_in,
+            token_one_accounts.0.decimals,
+            amount,
+            fee_discount_bps,
+        )?;
+        let amount_out = swap_amounts.amount_out;
+
+        self.deposit_to_pool(
+            token_one_accounts.0,
+            token_one_accounts.2,
+            token_one_accounts.1,
+            amount,
+            native_sol,
+            authority,
+            token_program,
+            system_program,
+        )?;
+
+        self.transfer_token_from_pool(
+            token_two_accounts.1,
+            token_two_accounts.2,
+            amount_out,
+            token_program,
+        )?;
+
+        Ok(swap_amounts)
+    }
+
+    fn settle_swap(
+        &mut self,
+        dex_configuration_account: &Account<'info, DexConfiguration>,
+        token_one_accounts: (&Account<'info, Mint>, &mut Account<'info, TokenAccount>),
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+        amount_in: u64,
+        min_amount_out: u64,
+        recipient_token_account: &Account<'info, TokenAccount>,
+        keeper_token_account: Option<&Account<'info, TokenAccount>>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<SwapAmounts> {
+        let (mint_in, pool_token_account_one) = token_one_accounts;
+
+        let swap_amounts = self.apply_swap(
+            dex_configuration_account,
+            self.is_token_one(mint_in.key()),
+            mint_in.decimals,
+            amount_in,
+            0_u16,
+        )?;
+
+        let keeper_fee = match keeper_token_account {
+            Some(_) => escrow::keeper_fee(swap_amounts.amount_out),
+            None => 0_u64,
+        };
+        let amount_out = swap_amounts.amount_out - keeper_fee;
+
+        if amount_out < min_amount_out {
+            return err!(DexProgramError::LimitPriceNotReached);
+        }
+
+        self.transfer_token_from_pool(
+            pool_token_account_two,
+            recipient_token_account,
+            amount_out,
+            token_program,
+        )?;
+
+        if let Some(keeper_token_account) = keeper_token_account {
+            if keeper_fee > 0 {
+                self.transfer_token_from_pool(
+                    pool_token_account_two,
+                    keeper_token_account,
+                    keeper_fee,
+                    token_program,
+                )?;
+            }
+        }
+
+        self.check_vault_balances(pool_token_account_one, pool_token_account_two)?;
+
+        Ok(swap_amounts)
+    }
+
+    fn check_vault_balances(
+        &self,
+        pool_token_account_one: &mut Account<'info, TokenAccount>,
+        pool_token_account_two: &mut Account<'info, TokenAccount>,
+    ) -> Result<()> {
+        pool_token_account_one.reload()?;
+        pool_token_account_two.reload()?;
+
+        let (balance_one, balance_two) = if self.is_token_one(pool_token_account_one.mint) {
+            (pool_token_account_one.amount, pool_token_account_two.amount)
+        } else {
+            (pool_token_account_two.amount, pool_token_account_one.amount)
+        };
+
+        let (owed_one, owed_two) = self.committed_balances()?;
+
+        if balance_one < owed_one || balance_two < owed_two {
+            return err!(DexProgramError::VaultBalanceBelowReserves);
+        }
+
+        Ok(())
+    }
+
+    fn close_pool_token_account(
+        &self,
+        account: &Account<'info, TokenAccount>,
+        destination: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::CloseAccount {
+                account: account.to_account_info(),
+                destination: destination.clone(),
+                authority: self.to_account_info(),
+            },
+            &[&[
+                LiquidityPool::POOL_SEED_PREFIX.as_bytes(),
+                LiquidityPool::generate_seed(self.token_one.key(), self.token_two.key()).as_bytes(),
+                &[self.bump],
+            ]],
+        ))?;
+
+        Ok(())
+    }
+
+    fn transfer_token_from_pool(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.to_account_info(),
+                },
+                &[&[
+                    LiquidityPool::POOL_SEED_PREFIX.as_bytes(),
+                    LiquidityPool::generate_seed(self.token_one.key(), self.token_two.key())
+                        .as_bytes(),
+                    &[self.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+
+    fn transfer_token_to_pool(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        transfer_from_user(from, to, amount, authority, token_program)
+    }
+
+    fn transfer_sol_to_pool(
+        &self,
+        from: &Signer<'info>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        system_program: &Program<'info, System>,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        wrap_sol_from_user(from, to, amount, system_program, token_program)
+    }
+
+    fn deposit_to_pool(
+        &self,
+        mint: &Account<'info, Mint>,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        native_sol: bool,
+        authority: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> Result<()> {
+        if !native_sol || !mint.key().eq(&native_mint::ID) {
+            return self.transfer_token_to_pool(from, to, amount, authority, token_program);
+        }
+
+        // The lamports are wrapped in the pool's temporary wrapped SOL account
+        // first and move on to the vault from there.
+        self.transfer_sol_to_pool(authority, from, amount, system_program, token_program)?;
+        self.transfer_token_from_pool(from, to, amount, token_program)
+    }
+}
@@ -0,0 +1,116 @@
+use crate::errors::DexProgramError;
+use crate::state::LiquidityPool;
+use anchor_lang::prelude::*;
+
+// Running sum of the spot price weighted by the seconds it was in effect
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct Observation {
+    pub timestamp: i64,
+    pub price_cumulative: f64,
+}
+
+impl Observation {
+    // Timestamp (8) + price cumulative (8)
+    pub const SIZE: usize = 8 + 8;
+}
+
+// Ring buffer of price observations used to derive a time weighted average
+// price. The accumulator only ever sees the price before a reserve change, so a
+// price pushed within a single transaction carries no weight.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct PriceOracle {
+    pub price_cumulative: f64,
+    pub last_update: i64,
+    pub index: u32,
+    pub observations: [Observation; PriceOracle::OBSERVATION_SLOTS],
+}
+
+impl PriceOracle {
+    pub const OBSERVATION_SLOTS: usize = 32;
+
+    // Minimum number of seconds between two observations
+    pub const OBSERVATION_PERIOD: i64 = 60;
+
+    // Price cumulative (8) + last update (8) + index (4) + observations (16 * 32)
+    pub const SIZE: usize = 8 + 8 + 4 + Observation::SIZE * Self::OBSERVATION_SLOTS;
+
+    // Accumulates `price` for the time since the last update, `price` being the
+    // spot price right before the reserves change.
+    pub fn update(&mut self, price: f64, now: i64) {
+        // An empty pool has no price, its history starts over once it is funded
+        if price <= 0_f64 {
+            *self = Self::default();
+            return;
+        }
+
+        if self.last_update > 0 {
+            let elapsed = now.saturating_sub(self.last_update);
+
+            if elapsed <= 0 {
+                return;
+            }
+
+            self.price_cumulative += price * elapsed as f64;
+        }
+
+        self.last_update = now;
+
+        let latest = self.observations[self.index as usize];
+
+        if latest.timestamp == 0 || now - latest.timestamp >= Self::OBSERVATION_PERIOD {
+            if latest.timestamp != 0 {
+                self.index = (self.index + 1) % Self::OBSERVATION_SLOTS as u32;
+            }
+
+            self.observations[self.index as usize] = Observation {
+                timestamp: now,
+                price_cumulative: self.price_cumulative,
+            };
+        }
+    }
+
+    // Average price over at least the last `window` seconds, `None` while the
+    // oracle does not reach back that far.
+    pub fn twap(&self, price: f64, now: i64, window: i64) -> Option<f64> {
+        if self.last_update == 0 {
+            return None;
+        }
+
+        let price_cumulative =
+            self.price_cumulative + price * now.saturating_sub(self.last_update).max(0) as f64;
+
+        let observation = self
+            .observations
+            .iter()
+            .filter(|observation| {
+                observation.timestamp != 0 && observation.timestamp <= now - window
+            })
+            .max_by_key(|observation| observation.timestamp)?;
+
+        let elapsed = now - observation.timestamp;
+
+        if elapsed <= 0 {
+            return None;
+        }
+
+        Some((price_cumulative - observation.price_cumulative) / elapsed as f64)
+    }
+}
+
+impl LiquidityPool {
+    pub fn observe(&mut self, now: i64) {
+        let price = Self::spot_price(self.reserve_one, self.reserve_two);
+
+        self.oracle.update(price, now);
+    }
+
+    // Time weighted price of token one in token two over at least `window` seconds
+    pub fn twap(&self, now: i64, window: i64) -> Result<f64> {
+        let price = Self::spot_price(self.reserve_one, self.reserve_two);
+
+        match self.oracle.twap(price, now, window) {
+            Some(twap) if twap > 0_f64 => Ok(twap),
+            _ => err!(DexProgramError::OracleUnavailable),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::state::EscrowedOrder;
+use anchor_lang::prelude::*;
+
+// A limit order selling `remaining_amount_in` of the input mint into the pool for
+// at least `limit_price` output tokens per input token, both in base units.
+#[account]
+pub struct Order {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub order_id: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub remaining_amount_in: u64,
+    pub filled_amount_out: u64,
+    pub limit_price: f64,
+    pub expiry: i64,
+    pub bump: u8,
+}
+
+impl Order {
+    pub const SEED_PREFIX: &'static str = "order";
+
+    pub const ESCROW_SEED_PREFIX: &'static str = "order_escrow";
+
+    // Discriminator (8) + owner (32) + pool (32) + order id (8) + input mint (32)
+    // + output mint (32) + amount in (8) + remaining amount in (8)
+    // + filled amount out (8) + limit price (8) + expiry (8) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expiry
+    }
+}
+
+impl EscrowedOrder for Order {
+    fn seed_prefix() -> &'static str {
+        Self::SEED_PREFIX
+    }
+
+    fn pool_key(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn owner_key(&self) -> Pubkey {
+        self.owner
+    }
+
+    fn id(&self) -> u64 {
+        self.order_id
+    }
+
+    fn bump(&self) -> u8 {
+        self.bump
+    }
+}
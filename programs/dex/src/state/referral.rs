@@ -0,0 +1,50 @@
+use crate::errors::DexProgramError;
+use anchor_lang::prelude::*;
+
+// Cumulative referral activity of a referrer, kept per input mint so volume and
+// fees are always in a single token's units.
+#[account]
+pub struct ReferrerStats {
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    pub swap_count: u64,
+    pub volume: u128,
+    pub fees_earned: u128,
+    pub bump: u8,
+}
+
+impl ReferrerStats {
+    pub const SEED_PREFIX: &'static str = "referrer_stats";
+
+    // Discriminator (8) + referrer (32) + mint (32) + swap count (8) + volume (16)
+    // + fees earned (16) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 16 + 16 + 1;
+
+    pub fn new(referrer: Pubkey, mint: Pubkey, bump: u8) -> Self {
+        Self {
+            referrer,
+            mint,
+            swap_count: 0_u64,
+            volume: 0_u128,
+            fees_earned: 0_u128,
+            bump,
+        }
+    }
+
+    pub fn record(&mut self, volume: u64, fee: u64) -> Result<()> {
+        self.swap_count = self
+            .swap_count
+            .checked_add(1)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.volume = self
+            .volume
+            .checked_add(volume as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.fees_earned = self
+            .fees_earned
+            .checked_add(fee as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{errors::DexProgramError, state::SwapAmounts};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+// A firm quote from a market maker to buy exactly `amount_in` of the input mint
+// for `amount_out` of the output mint. Makers sign the serialized quote off chain
+// and takers submit it together with an Ed25519 program instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RfqQuote {
+    pub maker: Pubkey,
+    // Anyone may fill the quote when left as the default key
+    pub taker: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub expiry: i64,
+    pub nonce: u64,
+}
+
+impl RfqQuote {
+    // Makers approve this program address as delegate of the token accounts they
+    // settle from
+    pub const AUTHORITY_SEED: &'static str = "rfq_authority";
+
+    // Signature count (1) + padding (1) + a single set of offsets (2 * 7)
+    const ED25519_HEADER_SIZE: usize = 2 + 2 * 7;
+
+    // The signed message is bound to this program so a quote cannot be replayed
+    // against another deployment
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = crate::ID.to_bytes().to_vec();
+        message.extend(self.try_to_vec()?);
+
+        Ok(message)
+    }
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expiry
+    }
+
+    // The Ed25519 program instruction right before the current one must have
+    // verified the maker's signature over this quote. The precompile fails the
+    // whole transaction on a bad signature, here we only check what it verified.
+    pub fn verify_signature(&self, instructions_sysvar: &AccountInfo) -> Result<()> {
+        let current_index = load_current_index_checked(instructions_sysvar)? as usize;
+
+        if current_index == 0 {
+            return err!(DexProgramError::InvalidQuoteSignature);
+        }
+
+        let instruction = load_instruction_at_checked(current_index - 1, instructions_sysvar)?;
+        let data = &instruction.data;
+
+        if instruction.program_id != ed25519_program::ID
+            || data.len() < Self::ED25519_HEADER_SIZE
+            || data[0] != 1
+        {
+            return err!(DexProgramError::InvalidQuoteSignature);
+        }
+
+        let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+
+        // Signature, public key and message all have to live in the Ed25519
+        // instruction itself, which it marks with an instruction index of u16::MAX
+        if read_u16(4) != u16::MAX || read_u16(8) != u16::MAX || read_u16(14) != u16::MAX {
+            return err!(DexProgramError::InvalidQuoteSignature);
+        }
+
+        let public_key_offset = read_u16(6) as usize;
+        let message_offset = read_u16(10) as usize;
+        let message_size = read_u16(12) as usize;
+
+        let public_key = data.get(public_key_offset..public_key_offset + 32);
+        let message = data.get(message_offset..message_offset + message_size);
+
+        if public_key != Some(self.maker.as_ref()) || message != Some(&self.message()?[..]) {
+            return err!(DexProgramError::InvalidQuoteSignature);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RfqSwapAmounts {
+    pub swap_amounts: SwapAmounts,
+    // Whether the maker's quote settled the trade, otherwise the pool did
+    pub filled_by_quote: bool,
+}
+
+// Bitmap of the quote nonces a maker has had filled, split in pages so makers are
+// free to pick nonces from a large range.
+#[account]
+pub struct MakerNonces {
+    pub maker: Pubkey,
+    pub page: u64,
+    pub bitmap: [u64; MakerNonces::BITMAP_WORDS],
+    pub bump: u8,
+}
+
+impl MakerNonces {
+    pub const SEED_PREFIX: &'static str = "maker_nonces";
+
+    pub const BITMAP_WORDS: usize = 64;
+
+    pub const NONCES_PER_PAGE: u64 = 64 * Self::BITMAP_WORDS as u64;
+
+    // Discriminator (8) + maker (32) + page (8) + bitmap (8 * 64) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 8 + 8 * Self::BITMAP_WORDS + 1;
+
+    pub fn page_of(nonce: u64) -> u64 {
+        nonce / Self::NONCES_PER_PAGE
+    }
+
+    fn bit(nonce: u64) -> (usize, u64) {
+        let index = nonce % Self::NONCES_PER_PAGE;
+
+        ((index / 64) as usize, 1_u64 << (index % 64))
+    }
+
+    pub fn is_used(&self, nonce: u64) -> bool {
+        let (word, mask) = Self::bit(nonce);
+
+        self.bitmap[word] & mask != 0
+    }
+
+    pub fn mark_used(&mut self, nonce: u64) {
+        let (word, mask) = Self::bit(nonce);
+
+        self.bitmap[word] |= mask;
+    }
+}
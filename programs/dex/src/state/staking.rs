@@ -0,0 +1,93 @@
+use crate::errors::DexProgramError;
+use anchor_lang::prelude::*;
+use std::cmp;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct FeeDiscountTier {
+    pub min_stake: u64,
+    pub discount_bps: u16,
+}
+
+impl FeeDiscountTier {
+    // Min stake (8) + discount bps (2)
+    pub const SIZE: usize = 8 + 2;
+}
+
+#[account]
+pub struct StakeAccount {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub warming_amount: u64,
+    pub warmup_end: i64,
+    pub bump: u8,
+    pub reserved: [u8; 32],
+}
+
+impl StakeAccount {
+    pub const SEED_PREFIX: &'static str = "stake";
+
+    pub const STAKE_VAULT_SEED: &'static str = "stake_vault";
+
+    pub const STAKE_VAULT_AUTHORITY_SEED: &'static str = "stake_vault_authority";
+
+    // Newly staked tokens only count towards fee discounts after a day, so they
+    // cannot be staked and unstaked around a single swap.
+    pub const WARMUP_PERIOD: i64 = 24 * 60 * 60;
+
+    // Discriminator (8) + owner (32) + amount (8) + warming amount (8) + warmup end (8)
+    // + bump (1) + reserved (32)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 8 + 8 + 8 + 1 + 32;
+
+    pub fn active_stake(&self, now: i64) -> u64 {
+        if now >= self.warmup_end {
+            self.amount
+        } else {
+            self.amount - self.warming_amount
+        }
+    }
+
+    // Only the added tokens warm up, stake that is already active stays active.
+    // Tokens still warming up are averaged with the new ones so a small top-up
+    // barely delays them.
+    pub fn add_stake(&mut self, amount: u64, now: i64) -> Result<()> {
+        let warming_amount = if now >= self.warmup_end {
+            0_u64
+        } else {
+            self.warming_amount
+        };
+
+        let total_warming = warming_amount
+            .checked_add(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        let remaining_warmup = ((warming_amount as u128)
+            * (cmp::max(self.warmup_end - now, 0) as u128)
+            + (amount as u128) * (Self::WARMUP_PERIOD as u128))
+            / total_warming as u128;
+
+        self.amount = self
+            .amount
+            .checked_add(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.warming_amount = total_warming;
+        self.warmup_end = now.saturating_add(remaining_warmup as i64);
+
+        Ok(())
+    }
+
+    // Unstaked tokens come out of what is still warming up first
+    pub fn remove_stake(&mut self, amount: u64, now: i64) -> Result<()> {
+        if amount == 0 || amount > self.amount {
+            return err!(DexProgramError::InvalidAmount);
+        }
+
+        if now >= self.warmup_end {
+            self.warming_amount = 0_u64;
+        }
+
+        self.amount -= amount;
+        self.warming_amount = self.warming_amount.saturating_sub(amount);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1305 @@
+use super::*;
+use anchor_lang::solana_program::{clock::Clock, program_stubs};
+use anchor_lang::Discriminator;
+use std::cell::Cell;
+use std::sync::Once;
+
+thread_local! {
+    static NOW: Cell<i64> = const { Cell::new(0) };
+}
+
+struct ClockStubs;
+
+impl program_stubs::SyscallStubs for ClockStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW.with(|now| now.get()),
+            ..Clock::default()
+        };
+
+        unsafe { *(var_addr as *mut Clock) = clock };
+
+        0
+    }
+}
+
+// Makes `Clock::get` return `now` on the calling test's thread
+pub fn set_clock(now: i64) {
+    static STUBS: Once = Once::new();
+
+    STUBS.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(ClockStubs));
+    });
+
+    NOW.with(|cell| cell.set(now));
+}
+
+// A program owned account of `size` bytes holding an all zero `T`
+pub fn zeroed_account<T>(size: usize) -> Account<'static, T>
+where
+    T: AccountSerialize + AccountDeserialize + Discriminator + Owner + Clone,
+{
+    let key = Box::leak(Box::new(Pubkey::new_unique()));
+    let owner = Box::leak(Box::new(T::owner()));
+    let lamports = Box::leak(Box::new(0_u64));
+    let data = Box::leak(vec![0_u8; size].into_boxed_slice());
+    data[..8].copy_from_slice(&T::discriminator());
+
+    let info = Box::leak(Box::new(AccountInfo::new(
+        key, false, true, lamports, data, owner, false, 0,
+    )));
+
+    Account::try_from(info).unwrap()
+}
+
+#[test]
+fn grant_shares_increases_total_supply() {
+    set_clock(0);
+
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut liquidity_provider =
+        zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    pool.total_supply = 1_000;
+    pool.grant_shares(&mut liquidity_provider, 250).unwrap();
+
+    assert_eq!(pool.total_supply, 1_250);
+    assert_eq!(liquidity_provider.shares, 250);
+
+    pool.remove_shares(&mut liquidity_provider, 250).unwrap();
+
+    assert_eq!(pool.total_supply, 1_000);
+    assert_eq!(liquidity_provider.shares, 0);
+}
+
+#[test]
+fn dynamic_fee_follows_decaying_volatility() {
+    let dex_configuration = DexConfiguration::new(0.3, Pubkey::new_unique());
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+
+    assert_eq!(pool.current_fee(&dex_configuration, 0), 0.3);
+
+    pool.dynamic_fee_enabled = true;
+    pool.min_fee = 0.1;
+    pool.max_fee = 1.0;
+
+    pool.update_volatility(1.0, 1.05, 0).unwrap();
+    assert!((pool.current_fee(&dex_configuration, 0) - 0.55).abs() < 1e-9);
+
+    // Half of the movement is forgotten after every half life
+    let half_life = LiquidityPool::VOLATILITY_HALF_LIFE;
+    assert!((pool.current_fee(&dex_configuration, half_life) - 0.325).abs() < 1e-9);
+
+    // Large movements saturate at the max fee
+    pool.update_volatility(1.0, 2.0, half_life).unwrap();
+    assert_eq!(pool.current_fee(&dex_configuration, half_life), 1.0);
+
+    // A staking discount applies to the dynamic fee as well
+    assert!((pool.discounted_fee(&dex_configuration, half_life, 2_500) - 0.75).abs() < 1e-9);
+}
+
+#[test]
+fn native_sol_is_only_supported_on_wrapped_sol_pools() {
+    let mut pool = LiquidityPool::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        0,
+        Pubkey::new_unique(),
+    );
+
+    assert!(!pool.has_native_mint());
+
+    pool.token_two = native_mint::ID;
+
+    assert!(pool.has_native_mint());
+}
+
+#[test]
+fn exact_out_quotes_the_smallest_sufficient_input() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.reserve_one = 1_000_000_000;
+    pool.reserve_two = 2_000_000_000;
+
+    for is_token_one_in in [true, false] {
+        let quote = pool
+            .quote_swap_exact_out(0.3, is_token_one_in, 6, 10_000_000)
+            .unwrap();
+
+        assert!(quote.amount_out >= 10_000_000);
+
+        let lower_quote = pool
+            .quote_swap_exact_in(0.3, is_token_one_in, 6, quote.amount_in - 1)
+            .unwrap();
+
+        assert!(lower_quote.amount_out < 10_000_000);
+    }
+
+    assert!(pool
+        .quote_swap_exact_out(0.3, true, 6, pool.reserve_two)
+        .is_err());
+}
+
+#[test]
+fn liquidity_quotes_are_proportional_to_the_reserves() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.total_supply = 1_000;
+    pool.reserve_one = 1_000_000;
+    pool.reserve_two = 4_000_000;
+
+    // The smaller side decides the shares of an unbalanced deposit
+    assert_eq!(
+        pool.quote_add_liquidity(100_000, 800_000, 6, 6).unwrap(),
+        100
+    );
+    assert_eq!(
+        pool.quote_remove_liquidity(100).unwrap(),
+        (100_000, 400_000)
+    );
+
+    assert!(pool.quote_remove_liquidity(1_001).is_err());
+    assert!(pool.quote_remove_liquidity(0).is_err());
+}
+
+#[test]
+fn guardian_cannot_halt_withdrawals() {
+    let admin = Pubkey::new_unique();
+    let guardian = Pubkey::new_unique();
+
+    let mut dex_configuration = DexConfiguration::new(0.3, admin);
+    dex_configuration.guardian = guardian;
+
+    assert!(dex_configuration.can_pause(admin, PAUSE_ALL));
+    assert!(dex_configuration.can_pause(guardian, PAUSE_SWAPS | PAUSE_DEPOSITS));
+    assert!(!dex_configuration.can_pause(guardian, PAUSE_WITHDRAWALS));
+    assert!(!dex_configuration.can_pause(Pubkey::new_unique(), PAUSE_SWAPS));
+}
+
+#[test]
+fn pool_is_paused_by_either_flag_set() {
+    let mut dex_configuration = DexConfiguration::new(0.3, Pubkey::new_unique());
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+
+    assert!(!pool.is_paused(&dex_configuration, PAUSE_SWAPS));
+
+    pool.paused = PAUSE_DEPOSITS;
+    dex_configuration.paused = PAUSE_SWAPS;
+
+    assert!(pool.is_paused(&dex_configuration, PAUSE_SWAPS));
+    assert!(pool.is_paused(&dex_configuration, PAUSE_DEPOSITS));
+    assert!(!pool.is_paused(&dex_configuration, PAUSE_WITHDRAWALS));
+}
+
+#[test]
+fn circuit_breaker_limits_movement_per_slot_and_window() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.max_slot_price_movement_bps = 100;
+    pool.max_window_price_movement_bps = 250;
+    pool.price_window_slots = 10;
+
+    // Movement within a slot adds up against the price the slot started from
+    pool.check_circuit_breaker(1.0, 1.005, 100).unwrap();
+    assert!(pool.check_circuit_breaker(1.005, 1.02, 100).is_err());
+
+    // A new slot starts from its own price, but the window remembers
+    pool.check_circuit_breaker(1.005, 1.014, 101).unwrap();
+    pool.check_circuit_breaker(1.014, 1.023, 102).unwrap();
+    assert!(pool.check_circuit_breaker(1.023, 1.03, 103).is_err());
+
+    // Once the window has passed the movement is measured afresh
+    pool.check_circuit_breaker(1.023, 1.03, 110).unwrap();
+}
+
+#[test]
+fn trade_limits_cap_size_and_price_impact() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.reserve_one = 1_000_000;
+    pool.reserve_two = 1_000_000;
+
+    let trade = |amount_in: u64| pool.quote_swap_exact_in(0.0, true, 6, amount_in).unwrap();
+    let (small_trade, large_trade) = (trade(1_000), trade(60_000));
+
+    pool.check_trade_limits(true, &large_trade).unwrap();
+
+    pool.max_trade_size_bps = 500;
+    pool.check_trade_limits(true, &small_trade).unwrap();
+    assert!(pool.check_trade_limits(true, &large_trade).is_err());
+
+    pool.max_trade_size_bps = 0;
+    pool.max_price_impact_bps = 100;
+    pool.check_trade_limits(true, &small_trade).unwrap();
+    assert!(pool.check_trade_limits(true, &large_trade).is_err());
+}
+
+#[test]
+fn quoted_swaps_are_held_to_the_circuit_breaker() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.reserve_one = 1_000_000;
+    pool.reserve_two = 1_000_000;
+    pool.max_slot_price_movement_bps = 100;
+
+    // Quotes run the breaker on a copy, so the account keeps its reference price
+    let mut copy = LiquidityPool::clone(&pool);
+    let price_movement = |pool: &LiquidityPool, amount_in: u64| {
+        let trade = pool.quote_swap_exact_in(0.0, true, 6, amount_in).unwrap();
+        let (reserve_one, reserve_two) = pool.reserves_after_swap(true, &trade).unwrap();
+
+        (
+            LiquidityPool::spot_price(pool.reserve_one, pool.reserve_two),
+            LiquidityPool::spot_price(reserve_one, reserve_two),
+        )
+    };
+
+    let (price_before, price_after) = price_movement(&copy, 1_000);
+    copy.check_circuit_breaker(price_before, price_after, 100)
+        .unwrap();
+
+    let (price_before, price_after) = price_movement(&copy, 60_000);
+    assert!(copy
+        .check_circuit_breaker(price_before, price_after, 100)
+        .is_err());
+    assert_eq!(pool.reference_slot, 0);
+}
+
+fn serialized_size<T: AccountSerialize>(account: &T) -> usize {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+
+    data.len()
+}
+
+#[test]
+fn account_sizes_match_the_current_layouts() {
+    let owner = Pubkey::new_unique();
+
+    assert_eq!(
+        serialized_size(&DexConfiguration::new(0.3, owner)),
+        DexConfiguration::ACCOUNT_SIZE
+    );
+    assert_eq!(
+        serialized_size(&LiquidityPool::new(owner, owner, 0, owner)),
+        LiquidityPool::ACCOUNT_SIZE
+    );
+
+    let liquidity_provider = zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+    assert_eq!(
+        serialized_size(&*liquidity_provider),
+        LiquidityProvider::ACCOUNT_SIZE
+    );
+}
+
+#[test]
+fn transferred_shares_keep_the_total_supply() {
+    set_clock(0);
+
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut from = zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+    let mut to = zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    pool.grant_shares(&mut from, 1_000).unwrap();
+    pool.transfer_shares(&mut from, &mut to, 400).unwrap();
+
+    assert_eq!(pool.total_supply, 1_000);
+    assert_eq!((from.shares, to.shares), (600, 400));
+
+    assert!(pool.transfer_shares(&mut from, &mut to, 0).is_err());
+    assert!(pool.transfer_shares(&mut from, &mut to, 601).is_err());
+}
+
+#[test]
+fn delegates_act_within_their_allowance() {
+    let owner = Pubkey::new_unique();
+    let delegate = Pubkey::new_unique();
+    let mut liquidity_provider =
+        zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    // Without a delegate only the owner may act, even for the default key
+    assert!(liquidity_provider.is_owner_or_delegate(owner, owner));
+    assert!(!liquidity_provider.is_owner_or_delegate(owner, Pubkey::default()));
+
+    liquidity_provider.delegate = delegate;
+    liquidity_provider.delegated_shares = 100;
+
+    assert!(liquidity_provider.is_owner_or_delegate(owner, delegate));
+
+    liquidity_provider.spend_delegated_shares(60).unwrap();
+    assert!(liquidity_provider.spend_delegated_shares(41).is_err());
+
+    liquidity_provider.spend_delegated_shares(40).unwrap();
+    assert_eq!(liquidity_provider.delegated_shares, 0);
+}
+
+#[test]
+fn locked_shares_cannot_be_removed_until_the_lock_ends() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut liquidity_provider =
+        zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    set_clock(0);
+    pool.grant_shares(&mut liquidity_provider, 1_000).unwrap();
+    liquidity_provider.locked_shares = 600;
+    liquidity_provider.lock_until = 100;
+
+    assert_eq!(liquidity_provider.unlocked_shares(99), 400);
+    assert!(pool.remove_shares(&mut liquidity_provider, 401).is_err());
+    pool.remove_shares(&mut liquidity_provider, 400).unwrap();
+
+    set_clock(100);
+    assert_eq!(liquidity_provider.unlocked_shares(100), 600);
+    pool.remove_shares(&mut liquidity_provider, 600).unwrap();
+}
+
+#[test]
+fn fees_are_shared_by_the_shares_held_when_they_were_paid() {
+    set_clock(0);
+
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut early = zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+    let mut late = zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    // Fee growth is a wrapping counter, accrual has to survive the wrap
+    pool.fee_growth_global_one = u128::MAX - 1;
+    pool.fee_growth_global_two = u128::MAX - 1;
+
+    pool.grant_shares(&mut early, 750).unwrap();
+    pool.collect_fee(true, 3_000).unwrap();
+
+    pool.grant_shares(&mut late, 250).unwrap();
+    pool.collect_fee(true, 1_000).unwrap();
+    pool.collect_fee(false, 2_000).unwrap();
+
+    pool.accrue_fees(&mut early).unwrap();
+    pool.accrue_fees(&mut late).unwrap();
+
+    assert_eq!(pool.fee_reserve_one, 4_000);
+    assert_eq!(pool.fee_reserve_two, 2_000);
+
+    // Fixed point rounding only ever rounds in favour of the pool
+    assert!((3_749..=3_750).contains(&early.fees_owed_one));
+    assert!((249..=250).contains(&late.fees_owed_one));
+    assert!((1_499..=1_500).contains(&early.fees_owed_two));
+    assert!((499..=500).contains(&late.fees_owed_two));
+
+    assert_eq!(pool.fees_owed_one, early.fees_owed_one + late.fees_owed_one);
+    assert_eq!(pool.fees_owed_two, early.fees_owed_two + late.fees_owed_two);
+}
+
+#[test]
+fn released_fees_leave_only_dust_in_the_fee_reserves() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.fee_reserve_one = 1_001;
+    pool.fee_reserve_two = 2_001;
+    pool.fees_owed_one = 1_000;
+    pool.fees_owed_two = 2_000;
+
+    pool.release_fees(1_000, 2_000).unwrap();
+
+    assert_eq!((pool.fees_owed_one, pool.fees_owed_two), (0, 0));
+    assert_eq!((pool.fee_reserve_one, pool.fee_reserve_two), (1, 1));
+
+    // Nothing beyond what is owed can be released
+    assert!(pool.release_fees(1, 0).is_err());
+}
+
+#[test]
+fn reward_emissions_are_capped_by_the_funded_amount() {
+    let mut reward_vault = RewardVault {
+        mint: Pubkey::new_unique(),
+        emission_rate: 10,
+        end_time: 1_000,
+        last_update: 0,
+        reward_per_share: 0,
+        undistributed: 500,
+    };
+
+    // Nothing is emitted while no shares are outstanding
+    reward_vault.update(0, 20).unwrap();
+    assert_eq!(reward_vault.undistributed, 500);
+    assert_eq!(reward_vault.last_update, 20);
+
+    reward_vault.update(100, 30).unwrap();
+    assert_eq!(reward_vault.undistributed, 400);
+    assert_eq!(
+        reward_vault.reward_per_share,
+        1_u128 << LiquidityPool::FEE_GROWTH_SHIFT
+    );
+
+    // Emissions stop once the funded amount runs out
+    reward_vault.update(100, 900).unwrap();
+    assert_eq!(reward_vault.undistributed, 0);
+}
+
+#[test]
+fn rewards_accrue_to_shares_held_while_emitted() {
+    set_clock(0);
+
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut liquidity_provider =
+        zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    pool.rewards[0] = RewardVault {
+        mint: Pubkey::new_unique(),
+        emission_rate: 10,
+        end_time: 1_000,
+        undistributed: 10_000,
+        ..RewardVault::default()
+    };
+
+    pool.grant_shares(&mut liquidity_provider, 400).unwrap();
+
+    pool.update_rewards(100).unwrap();
+    pool.accrue_rewards(&mut liquidity_provider).unwrap();
+
+    assert_eq!(liquidity_provider.rewards_owed[0], 1_000);
+    assert_eq!(pool.rewards[0].undistributed, 9_000);
+
+    // The pool keeps owing what was accrued until it is paid out
+    assert_eq!(pool.rewards_owed[0], 1_000);
+    pool.release_rewards(0, 1_000).unwrap();
+    assert_eq!(pool.rewards_owed[0], 0);
+    assert!(pool.release_rewards(0, 1).is_err());
+}
+
+#[test]
+fn add_emissions_keeps_the_scheduled_stream() {
+    let mut reward_vault = RewardVault {
+        emission_rate: 10,
+        end_time: 1_000,
+        last_update: 500,
+        undistributed: 5_000,
+        ..RewardVault::default()
+    };
+
+    // 5_000 still scheduled until 1_000 plus 3_000 new over 100 seconds, the
+    // later end time wins and both amounts are spread over it
+    reward_vault.add_emissions(3_000, 500, 100).unwrap();
+
+    assert_eq!(reward_vault.end_time, 1_000);
+    assert_eq!(reward_vault.emission_rate, 16);
+    assert_eq!(reward_vault.undistributed, 8_000);
+}
+
+#[test]
+fn add_emissions_restarts_an_ended_stream() {
+    let mut reward_vault = RewardVault {
+        emission_rate: 10,
+        end_time: 400,
+        last_update: 500,
+        ..RewardVault::default()
+    };
+
+    reward_vault.add_emissions(3_000, 500, 100).unwrap();
+
+    assert_eq!(reward_vault.end_time, 600);
+    assert_eq!(reward_vault.emission_rate, 30);
+    assert_eq!(reward_vault.undistributed, 3_000);
+}
+
+#[test]
+fn epoch_weights_reuse_the_slot_of_two_epochs_before() {
+    let mut weights = EpochWeights::default();
+
+    weights.add(4, 300).unwrap();
+    weights.add(5, 200).unwrap();
+    weights.sub(4, 100).unwrap();
+
+    assert_eq!(weights.weight_at(4), 200);
+    assert_eq!(weights.weight_at(5), 200);
+
+    // Epoch 6 overwrites the tally of epoch 4 instead of adding to it
+    weights.add(6, 50).unwrap();
+    assert_eq!(weights.weight_at(6), 50);
+    assert_eq!(weights.weight_at(4), 0);
+
+    assert!(weights.sub(6, 51).is_err());
+}
+
+#[test]
+fn voting_power_decays_linearly_until_the_lock_ends() {
+    let vote_escrow = VoteEscrow {
+        owner: Pubkey::new_unique(),
+        amount: 1_000,
+        lock_end: GaugeController::MAX_LOCK_DURATION,
+        vote_epoch: 0,
+        used_vote_bps: 0,
+        bump: 0,
+        reserved: [0_u8; 32],
+    };
+
+    assert_eq!(vote_escrow.voting_power(0), 1_000);
+    assert_eq!(
+        vote_escrow.voting_power(GaugeController::MAX_LOCK_DURATION / 4),
+        750
+    );
+    assert_eq!(
+        vote_escrow.voting_power(GaugeController::MAX_LOCK_DURATION),
+        0
+    );
+    assert_eq!(
+        vote_escrow.voting_power(GaugeController::MAX_LOCK_DURATION + 1),
+        0
+    );
+
+    // Locks longer than the maximum don't grant more than the locked amount
+    assert_eq!(vote_escrow.voting_power(-100), 1_000);
+}
+
+#[test]
+fn epochs_start_at_the_controller_genesis() {
+    let gauge_controller = GaugeController::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        1_000,
+        100,
+        5_000,
+        0,
+    );
+
+    assert_eq!(gauge_controller.epoch_at(500), 0);
+    assert_eq!(gauge_controller.epoch_at(1_099), 0);
+    assert_eq!(gauge_controller.epoch_at(1_100), 1);
+    assert_eq!(gauge_controller.epoch_at(1_350), 3);
+}
+
+#[test]
+fn first_vault_deposit_pays_for_the_dead_shares() {
+    assert_eq!(
+        CompoundingVault::vault_shares_for_deposit(10_000, 0, 0).unwrap(),
+        10_000 - CompoundingVault::DEAD_SHARES
+    );
+    assert_eq!(
+        CompoundingVault::vault_shares_for_deposit(CompoundingVault::DEAD_SHARES, 0, 0).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn leftover_vault_liquidity_belongs_to_the_dead_shares() {
+    // Every depositor left, 2_000 liquidity shares were harvested after that
+    let vault_shares = CompoundingVault::vault_shares_for_deposit(10_000, 2_000, 0).unwrap();
+    assert_eq!(vault_shares, 5_000);
+
+    // The new depositor can't take the leftover with them
+    let shares =
+        CompoundingVault::shares_for_withdrawal(vault_shares, 12_000, vault_shares).unwrap();
+    assert_eq!(shares, 10_000);
+}
+
+#[test]
+fn vault_shares_track_the_vault_position() {
+    // 9_000 minted plus the dead shares back 20_000 liquidity shares after compounding
+    let vault_shares = CompoundingVault::vault_shares_for_deposit(4_000, 20_000, 9_000).unwrap();
+    assert_eq!(vault_shares, 2_000);
+
+    let shares = CompoundingVault::shares_for_withdrawal(9_000, 20_000, 9_000).unwrap();
+    assert_eq!(shares, 18_000);
+}
+
+#[test]
+fn donations_to_the_vault_mostly_go_to_the_dead_shares() {
+    // The attacker is the first depositor and keeps a single vault share
+    let attacker_shares =
+        CompoundingVault::vault_shares_for_deposit(CompoundingVault::DEAD_SHARES + 1, 0, 0)
+            .unwrap();
+    assert_eq!(attacker_shares, 1);
+
+    // Donating a million liquidity shares to the vault position
+    let vault_liquidity_shares = CompoundingVault::DEAD_SHARES + 1 + 1_000_000;
+
+    let victim_shares =
+        CompoundingVault::vault_shares_for_deposit(500_000, vault_liquidity_shares, 1).unwrap();
+    assert_eq!(victim_shares, 499);
+
+    // Withdrawing everything the attacker owns returns a fraction of the donation
+    let attacker_withdrawal = CompoundingVault::shares_for_withdrawal(
+        attacker_shares,
+        vault_liquidity_shares + 500_000,
+        attacker_shares + victim_shares,
+    )
+    .unwrap();
+    assert!(attacker_withdrawal < 1_100);
+}
+
+#[test]
+fn pending_vault_earnings_are_valued_at_the_pool_price() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    let mut vault_liquidity_provider =
+        zeroed_account::<LiquidityProvider>(LiquidityProvider::ACCOUNT_SIZE);
+
+    pool.token_one = Pubkey::new_unique();
+    pool.token_two = Pubkey::new_unique();
+    pool.total_supply = 1_000;
+    pool.reserve_one = 10_000;
+    pool.reserve_two = 40_000;
+    pool.rewards[0].mint = pool.token_two;
+    pool.rewards[1].mint = Pubkey::new_unique();
+
+    vault_liquidity_provider.fees_owed_one = 100;
+    vault_liquidity_provider.fees_owed_two = 200;
+    vault_liquidity_provider.rewards_owed = [200, 5_000, 0];
+
+    // 100 of token one and 400 of token two are 10 shares' worth of each reserve,
+    // together the value of 10 shares. The reward in a foreign token is not counted.
+    assert_eq!(
+        CompoundingVault::pending_shares(&pool, &vault_liquidity_provider).unwrap(),
+        10
+    );
+}
+
+#[test]
+fn harvest_swaps_about_half_of_the_excess() {
+    let pool = twamm_pool(1_000_000);
+
+    assert_eq!(pool.balancing_swap_amount(0.0, 500, 500), (true, 0));
+    assert_eq!(pool.balancing_swap_amount(0.0, 1_000, 0), (true, 499));
+    assert_eq!(pool.balancing_swap_amount(0.0, 0, 1_000), (false, 499));
+
+    // The fee taken on the swap is made up by swapping slightly more
+    let (is_token_one_in, swap_amount) = pool.balancing_swap_amount(1.0, 1_000, 0);
+    assert!(is_token_one_in);
+    assert!(swap_amount > 499 && swap_amount < 510);
+}
+
+#[test]
+fn referral_fee_is_capped_by_the_configured_maximum() {
+    let mut dex_configuration = DexConfiguration::new(0.3, Pubkey::new_unique());
+
+    // Referrals are off until the admin allows a fee
+    assert_eq!(dex_configuration.referral_fee(10_000, 0).unwrap(), 0);
+    assert!(dex_configuration.referral_fee(10_000, 1).is_err());
+
+    dex_configuration.max_referral_fee_bps = 50;
+
+    assert_eq!(dex_configuration.referral_fee(10_000, 50).unwrap(), 50);
+    assert_eq!(dex_configuration.referral_fee(199, 50).unwrap(), 0);
+    assert!(dex_configuration.referral_fee(10_000, 51).is_err());
+}
+
+#[test]
+fn referrer_stats_accumulate_every_referred_swap() {
+    let mut referrer_stats = ReferrerStats::new(Pubkey::new_unique(), Pubkey::new_unique(), 0);
+
+    referrer_stats.record(10_000, 50).unwrap();
+    referrer_stats.record(u64::MAX, 0).unwrap();
+
+    assert_eq!(referrer_stats.swap_count, 2);
+    assert_eq!(referrer_stats.volume, 10_000 + u64::MAX as u128);
+    assert_eq!(referrer_stats.fees_earned, 50);
+}
+
+#[test]
+fn topping_up_a_stake_keeps_the_active_part() {
+    let mut stake_account = zeroed_account::<StakeAccount>(StakeAccount::ACCOUNT_SIZE);
+
+    stake_account.add_stake(1_000, 0).unwrap();
+    assert_eq!(stake_account.active_stake(0), 0);
+
+    let warmed_up = StakeAccount::WARMUP_PERIOD;
+    assert_eq!(stake_account.active_stake(warmed_up), 1_000);
+
+    stake_account.add_stake(500, warmed_up).unwrap();
+    assert_eq!(stake_account.active_stake(warmed_up), 1_000);
+    assert_eq!(
+        stake_account.active_stake(warmed_up + StakeAccount::WARMUP_PERIOD),
+        1_500
+    );
+}
+
+#[test]
+fn topping_up_a_warming_stake_averages_the_warmup() {
+    let mut stake_account = zeroed_account::<StakeAccount>(StakeAccount::ACCOUNT_SIZE);
+
+    stake_account.add_stake(1_000, 0).unwrap();
+
+    // Half way through 1_000 more join, they warm up together in 3/4 of a period
+    let half_way = StakeAccount::WARMUP_PERIOD / 2;
+    stake_account.add_stake(1_000, half_way).unwrap();
+
+    assert_eq!(
+        stake_account.warmup_end,
+        half_way + StakeAccount::WARMUP_PERIOD * 3 / 4
+    );
+    assert_eq!(stake_account.warming_amount, 2_000);
+}
+
+#[test]
+fn unstaking_takes_warming_tokens_first() {
+    let mut stake_account = zeroed_account::<StakeAccount>(StakeAccount::ACCOUNT_SIZE);
+
+    stake_account.add_stake(1_000, 0).unwrap();
+    stake_account
+        .add_stake(500, StakeAccount::WARMUP_PERIOD)
+        .unwrap();
+
+    stake_account
+        .remove_stake(700, StakeAccount::WARMUP_PERIOD)
+        .unwrap();
+
+    assert_eq!(stake_account.amount, 800);
+    assert_eq!(stake_account.active_stake(StakeAccount::WARMUP_PERIOD), 800);
+    assert!(stake_account
+        .remove_stake(801, StakeAccount::WARMUP_PERIOD)
+        .is_err());
+}
+
+#[test]
+fn fee_discount_is_the_best_tier_reached() {
+    let mut dex_configuration = DexConfiguration::new(0.3, Pubkey::new_unique());
+
+    dex_configuration.fee_discount_tiers[0] = FeeDiscountTier {
+        min_stake: 1_000,
+        discount_bps: 1_000,
+    };
+    dex_configuration.fee_discount_tiers[1] = FeeDiscountTier {
+        min_stake: 10_000,
+        discount_bps: 2_500,
+    };
+    // Tiers are not required to be sorted
+    dex_configuration.fee_discount_tiers[2] = FeeDiscountTier {
+        min_stake: 5_000,
+        discount_bps: 500,
+    };
+
+    assert_eq!(dex_configuration.fee_discount_bps(0), 0);
+    assert_eq!(dex_configuration.fee_discount_bps(999), 0);
+    assert_eq!(dex_configuration.fee_discount_bps(5_000), 1_000);
+    assert_eq!(dex_configuration.fee_discount_bps(10_000), 2_500);
+}
+
+#[test]
+fn unused_fee_discount_tiers_grant_nothing() {
+    let mut dex_configuration = DexConfiguration::new(0.3, Pubkey::new_unique());
+
+    // An unset tier has no minimum stake, it must not apply to every swap
+    dex_configuration.fee_discount_tiers[3].discount_bps = 10_000;
+
+    assert_eq!(dex_configuration.fee_discount_bps(0), 0);
+    assert_eq!(dex_configuration.fee_discount_bps(u64::MAX), 0);
+}
+
+#[test]
+fn keeper_fee_is_a_share_of_the_output() {
+    assert_eq!(keeper_fee(1_000_000), 1_000);
+    assert_eq!(keeper_fee(999), 0);
+    assert_eq!(keeper_fee(u64::MAX), u64::MAX / 1_000);
+}
+
+#[test]
+fn limit_orders_can_be_filled_until_their_expiry() {
+    let order = Order {
+        owner: Pubkey::new_unique(),
+        pool: Pubkey::new_unique(),
+        order_id: 0,
+        input_mint: Pubkey::new_unique(),
+        output_mint: Pubkey::new_unique(),
+        amount_in: 1_000,
+        remaining_amount_in: 1_000,
+        filled_amount_out: 0,
+        limit_price: 0.5,
+        expiry: 100,
+        bump: 0,
+    };
+
+    assert!(!order.is_expired(99));
+    assert!(!order.is_expired(100));
+    assert!(order.is_expired(101));
+}
+
+fn twamm_pool(reserve: u64) -> Account<'static, LiquidityPool> {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+
+    pool.total_supply = reserve;
+    pool.reserve_one = reserve;
+    pool.reserve_two = reserve;
+
+    pool
+}
+
+fn place_long_term_order(
+    pool: &mut LiquidityPool,
+    side: usize,
+    sell_rate: u64,
+    intervals: u64,
+) -> LongTermOrder {
+    let expiry_interval = pool.twamm.last_executed_interval + intervals;
+
+    pool.twamm.credit(side, sell_rate * intervals).unwrap();
+    pool.twamm
+        .add_order(side, sell_rate, expiry_interval)
+        .unwrap();
+
+    LongTermOrder {
+        owner: Pubkey::new_unique(),
+        pool: Pubkey::new_unique(),
+        order_id: 0,
+        input_mint: Pubkey::new_unique(),
+        output_mint: Pubkey::new_unique(),
+        sell_rate,
+        expiry_interval,
+        earnings_checkpoint: pool.twamm.order_pools[side].earnings_per_sell_rate,
+        bump: 0,
+    }
+}
+
+#[test]
+fn long_term_order_sells_every_interval_until_expiry() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    let mut order = place_long_term_order(&mut pool, 0, 1_000, 2);
+
+    pool.execute_virtual_orders(&dex_configuration, Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 1);
+    assert_eq!(pool.twamm.balances[0], 1_000);
+    assert_eq!(order.unsold_amount(&pool.twamm).unwrap(), 1_000);
+
+    pool.execute_virtual_orders(&dex_configuration, 5 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 5);
+    assert_eq!(pool.twamm.order_pools[0].sell_rate, 0);
+    assert_eq!(pool.twamm.balances[0], 0);
+    assert_eq!(pool.reserve_one, 1_002_000);
+    assert_eq!(pool.reserve_two, 1_000_000 - pool.twamm.balances[1]);
+    assert!(order.is_expired(&pool.twamm));
+
+    // Proceeds are rounded down, whatever they leave behind is dust
+    let proceeds = order.take_proceeds(&pool.twamm, 0).unwrap();
+    assert!(proceeds <= pool.twamm.balances[1]);
+    assert!(pool.twamm.balances[1] - proceeds <= 1);
+    assert!(proceeds > 1_990);
+}
+
+#[test]
+fn opposing_long_term_orders_are_matched() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    let mut order_one = place_long_term_order(&mut pool, 0, 1_000, 1);
+    let mut order_two = place_long_term_order(&mut pool, 1, 1_000, 1);
+
+    pool.execute_virtual_orders(&dex_configuration, Twamm::INTERVAL)
+        .unwrap();
+
+    // Equal flows at a 1:1 price never touch the curve
+    assert_eq!(pool.reserve_one, 1_000_000);
+    assert_eq!(pool.reserve_two, 1_000_000);
+    assert_eq!(order_one.take_proceeds(&pool.twamm, 0).unwrap(), 1_000);
+    assert_eq!(order_two.take_proceeds(&pool.twamm, 1).unwrap(), 1_000);
+}
+
+#[test]
+fn long_term_orders_wait_for_liquidity() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(0);
+    let order = place_long_term_order(&mut pool, 0, 1_000, 2);
+
+    pool.execute_virtual_orders(&dex_configuration, 3 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 0);
+    assert_eq!(pool.twamm.balances, [2_000, 0]);
+    assert_eq!(pool.reserve_one, 0);
+    assert_eq!(order.unsold_amount(&pool.twamm).unwrap(), 2_000);
+
+    // Once liquidity is back the missed intervals trade
+    pool.total_supply = 1_000_000;
+    pool.reserve_one = 1_000_000;
+    pool.reserve_two = 1_000_000;
+
+    pool.execute_virtual_orders(&dex_configuration, 3 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 3);
+    assert_eq!(pool.twamm.balances[0], 0);
+    assert_eq!(pool.reserve_one, 1_002_000);
+}
+
+#[test]
+fn long_gaps_are_executed_over_several_calls() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    place_long_term_order(&mut pool, 0, 1_000, 20);
+
+    let now = 40 * Twamm::INTERVAL;
+
+    for last_executed_interval in [8, 16] {
+        pool.execute_virtual_orders(&dex_configuration, now)
+            .unwrap();
+        assert_eq!(pool.twamm.last_executed_interval, last_executed_interval);
+    }
+
+    // Once the order has expired the rest is skipped in one go
+    pool.execute_virtual_orders(&dex_configuration, now)
+        .unwrap();
+    assert_eq!(pool.twamm.last_executed_interval, 40);
+    assert_eq!(pool.reserve_one, 1_020_000);
+}
+
+#[test]
+fn long_term_orders_wait_while_swaps_are_paused() {
+    let mut dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    place_long_term_order(&mut pool, 0, 1_000, 2);
+
+    dex_configuration.paused = PAUSE_SWAPS;
+    pool.execute_virtual_orders(&dex_configuration, 2 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 0);
+    assert_eq!(pool.twamm.balances, [2_000, 0]);
+    assert_eq!(pool.reserve_one, 1_000_000);
+
+    dex_configuration.paused = 0;
+    pool.execute_virtual_orders(&dex_configuration, 2 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.last_executed_interval, 2);
+    assert_eq!(pool.reserve_one, 1_002_000);
+}
+
+#[test]
+fn twamm_dust_is_swept_once_the_last_order_is_gone() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    let mut order = place_long_term_order(&mut pool, 0, 333, 3);
+
+    pool.execute_virtual_orders(&dex_configuration, 3 * Twamm::INTERVAL)
+        .unwrap();
+
+    let proceeds = order.take_proceeds(&pool.twamm, 0).unwrap();
+    pool.twamm.debit(1, proceeds).unwrap();
+
+    let reserve_two = pool.reserve_two;
+    let dust = pool.twamm.balances[1];
+
+    // Still open, nothing is swept
+    pool.sweep_twamm_dust().unwrap();
+    assert_eq!(pool.twamm.balances[1], dust);
+
+    pool.twamm
+        .remove_order(0, order.sell_rate, order.expiry_interval)
+        .unwrap();
+    pool.sweep_twamm_dust().unwrap();
+
+    assert_eq!(pool.twamm.balances, [0, 0]);
+    assert_eq!(pool.reserve_two, reserve_two + dust);
+}
+
+#[test]
+fn long_term_orders_share_proceeds_by_sell_rate() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    let mut small_order = place_long_term_order(&mut pool, 0, 1_000, 1);
+    let mut large_order = place_long_term_order(&mut pool, 0, 3_000, 1);
+
+    pool.execute_virtual_orders(&dex_configuration, Twamm::INTERVAL)
+        .unwrap();
+
+    let small_proceeds = small_order.take_proceeds(&pool.twamm, 0).unwrap();
+    let large_proceeds = large_order.take_proceeds(&pool.twamm, 0).unwrap();
+
+    assert!(small_proceeds > 0);
+    assert!(large_proceeds.abs_diff(3 * small_proceeds) <= 3);
+    assert!(small_proceeds + large_proceeds <= pool.twamm.balances[1]);
+
+    // Proceeds are only paid out once
+    assert_eq!(small_order.take_proceeds(&pool.twamm, 0).unwrap(), 0);
+}
+
+#[test]
+fn cancelled_long_term_orders_stop_selling() {
+    let dex_configuration = zeroed_account::<DexConfiguration>(DexConfiguration::ACCOUNT_SIZE);
+    let mut pool = twamm_pool(1_000_000);
+    let order = place_long_term_order(&mut pool, 0, 1_000, 4);
+
+    pool.execute_virtual_orders(&dex_configuration, Twamm::INTERVAL)
+        .unwrap();
+
+    let unsold_amount = order.unsold_amount(&pool.twamm).unwrap();
+    assert_eq!(unsold_amount, 3_000);
+
+    pool.twamm
+        .remove_order(0, order.sell_rate, order.expiry_interval)
+        .unwrap();
+    pool.twamm.debit(0, unsold_amount).unwrap();
+
+    pool.execute_virtual_orders(&dex_configuration, 4 * Twamm::INTERVAL)
+        .unwrap();
+
+    assert_eq!(pool.twamm.order_pools[0].sell_rate, 0);
+    assert_eq!(pool.twamm.balances[0], 0);
+    assert_eq!(pool.reserve_one, 1_001_000);
+}
+
+#[test]
+fn committed_balances_cover_reserves_fees_and_orders() {
+    let mut pool = zeroed_account::<LiquidityPool>(LiquidityPool::ACCOUNT_SIZE);
+    pool.reserve_one = 1_000;
+    pool.reserve_two = 2_000;
+    pool.fee_reserve_one = 30;
+    pool.fee_reserve_two = 40;
+    pool.twamm.balances = [500, 600];
+
+    assert_eq!(pool.committed_balances().unwrap(), (1_530, 2_640));
+
+    pool.fee_reserve_one = u64::MAX;
+    assert!(pool.committed_balances().is_err());
+}
+
+#[test]
+fn dca_cycles_are_due_until_none_remain() {
+    let mut position = DcaPosition {
+        owner: Pubkey::new_unique(),
+        pool: Pubkey::new_unique(),
+        position_id: 0,
+        input_mint: Pubkey::new_unique(),
+        output_mint: Pubkey::new_unique(),
+        amount_per_cycle: 1_000,
+        cycle_interval: 60,
+        cycles_remaining: 2,
+        min_amount_out: 0,
+        next_cycle_at: 100,
+        bump: 0,
+    };
+
+    assert!(!position.is_cycle_due(99));
+    assert!(position.is_cycle_due(100));
+    assert!(position.is_cycle_due(1_000));
+
+    position.cycles_remaining = 0;
+    assert!(!position.is_cycle_due(1_000));
+}
+
+#[test]
+fn twap_weights_prices_by_the_time_they_were_in_effect() {
+    let mut oracle = PriceOracle::default();
+
+    assert_eq!(oracle.twap(1.0, 1_000, 300), None);
+
+    oracle.update(1.0, 1_000);
+    assert_eq!(oracle.twap(1.0, 1_000, 300), None);
+
+    oracle.update(1.0, 1_300);
+    // A second update at the same time carries no weight
+    oracle.update(9.0, 1_300);
+
+    // 1.0 for 300 seconds, then 3.0 for the last 100 seconds
+    assert_eq!(oracle.twap(3.0, 1_400, 300), Some(1.5));
+    assert_eq!(oracle.twap(3.0, 1_400, 100), Some(3.0));
+    assert_eq!(oracle.twap(3.0, 1_400, 500), None);
+}
+
+#[test]
+fn oracle_observations_are_spaced_and_wrap_around() {
+    let mut oracle = PriceOracle::default();
+
+    oracle.update(1.0, 1_000);
+    oracle.update(1.0, 1_000 + PriceOracle::OBSERVATION_PERIOD - 1);
+    assert_eq!(oracle.index, 0);
+
+    oracle.update(1.0, 1_000 + PriceOracle::OBSERVATION_PERIOD);
+    assert_eq!(oracle.index, 1);
+
+    // Once every slot is used the oldest observation is overwritten
+    let mut now = 1_000 + PriceOracle::OBSERVATION_PERIOD;
+    for _ in 0..PriceOracle::OBSERVATION_SLOTS - 1 {
+        now += PriceOracle::OBSERVATION_PERIOD;
+        oracle.update(1.0, now);
+    }
+
+    assert_eq!(oracle.index, 0);
+    assert_eq!(oracle.twap(1.0, now, now - 1_000), None);
+    assert_eq!(
+        oracle.twap(1.0, now, now - 1_000 - PriceOracle::OBSERVATION_PERIOD),
+        Some(1.0)
+    );
+}
+
+#[test]
+fn emptied_pools_restart_their_price_history() {
+    let mut oracle = PriceOracle::default();
+
+    oracle.update(1.0, 1_000);
+    oracle.update(1.0, 1_300);
+    oracle.update(0.0, 1_400);
+
+    assert_eq!(oracle.last_update, 0);
+    assert_eq!(oracle.twap(1.0, 1_400, 300), None);
+}
+
+#[test]
+fn pool_twap_requires_a_long_enough_history() {
+    let mut pool = twamm_pool(1_000_000);
+
+    assert!(pool.twap(1_000, TriggerOrder::TWAP_WINDOW).is_err());
+
+    pool.observe(1_000);
+    assert!(pool.twap(1_200, TriggerOrder::TWAP_WINDOW).is_err());
+
+    // Swaps observe the price right before they change the reserves
+    pool.observe(1_300);
+    pool.reserve_two = 2_000_000;
+
+    // The doubled price has only been in effect for the last quarter
+    assert_eq!(pool.twap(1_400, TriggerOrder::TWAP_WINDOW).unwrap(), 1.25);
+}
+
+#[test]
+fn trigger_orders_fire_on_the_right_side_of_the_price() {
+    let mut trigger_order = TriggerOrder {
+        owner: Pubkey::new_unique(),
+        pool: Pubkey::new_unique(),
+        order_id: 0,
+        input_mint: Pubkey::new_unique(),
+        output_mint: Pubkey::new_unique(),
+        amount_in: 1_000,
+        trigger_price: 1.5,
+        is_take_profit: true,
+        min_amount_out: 0,
+        bump: 0,
+    };
+
+    assert!(!trigger_order.is_triggered(1.49));
+    assert!(trigger_order.is_triggered(1.5));
+    assert!(trigger_order.is_triggered(2.0));
+
+    trigger_order.is_take_profit = false;
+
+    assert!(trigger_order.is_triggered(1.49));
+    assert!(trigger_order.is_triggered(1.5));
+    assert!(!trigger_order.is_triggered(2.0));
+}
+
+#[test]
+fn maker_nonces_are_tracked_per_page() {
+    let mut maker_nonces = MakerNonces {
+        maker: Pubkey::new_unique(),
+        page: 1,
+        bitmap: [0_u64; MakerNonces::BITMAP_WORDS],
+        bump: 0,
+    };
+
+    assert_eq!(MakerNonces::page_of(0), 0);
+    assert_eq!(MakerNonces::page_of(MakerNonces::NONCES_PER_PAGE - 1), 0);
+    assert_eq!(MakerNonces::page_of(MakerNonces::NONCES_PER_PAGE), 1);
+
+    let first = MakerNonces::NONCES_PER_PAGE;
+    let last = 2 * MakerNonces::NONCES_PER_PAGE - 1;
+
+    maker_nonces.mark_used(first);
+    maker_nonces.mark_used(first + 65);
+    maker_nonces.mark_used(last);
+
+    assert!(maker_nonces.is_used(first));
+    assert!(!maker_nonces.is_used(first + 1));
+    assert!(!maker_nonces.is_used(first + 64));
+    assert!(maker_nonces.is_used(first + 65));
+    assert!(maker_nonces.is_used(last));
+
+    assert_eq!(maker_nonces.bitmap[0], 1);
+    assert_eq!(maker_nonces.bitmap[1], 1 << 1);
+    assert_eq!(maker_nonces.bitmap[MakerNonces::BITMAP_WORDS - 1], 1 << 63);
+
+    // Marking a nonce again leaves the bitmap unchanged
+    maker_nonces.mark_used(first);
+    assert_eq!(maker_nonces.bitmap[0], 1);
+}
+
+#[test]
+fn rfq_quotes_are_bound_to_the_program() {
+    let quote = RfqQuote {
+        maker: Pubkey::new_unique(),
+        taker: Pubkey::default(),
+        input_mint: Pubkey::new_unique(),
+        output_mint: Pubkey::new_unique(),
+        amount_in: 1_000,
+        amount_out: 990,
+        expiry: 100,
+        nonce: 7,
+    };
+
+    let message = quote.message().unwrap();
+
+    assert_eq!(&message[..32], crate::ID.as_ref());
+    assert_eq!(&message[32..], &quote.try_to_vec().unwrap()[..]);
+
+    assert!(!quote.is_expired(100));
+    assert!(quote.is_expired(101));
+}
+
+// An initialized token account of `mint`, or the mint itself with `is_mint`
+fn token_program_account<T>(mint: Pubkey, is_mint: bool) -> Account<'static, T>
+where
+    T: AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState, Mint};
+
+    let (key, data) = if is_mint {
+        let mut data = vec![0_u8; Mint::LEN];
+        let state = Mint {
+            is_initialized: true,
+            ..Mint::default()
+        };
+        Mint::pack(state, &mut data).unwrap();
+        (mint, data)
+    } else {
+        let mut data = vec![0_u8; SplAccount::LEN];
+        let state = SplAccount {
+            mint,
+            state: AccountState::Initialized,
+            ..SplAccount::default()
+        };
+        SplAccount::pack(state, &mut data).unwrap();
+        (Pubkey::new_unique(), data)
+    };
+
+    let key = Box::leak(Box::new(key));
+    let owner = Box::leak(Box::new(T::owner()));
+    let lamports = Box::leak(Box::new(0_u64));
+    let data = Box::leak(data.into_boxed_slice());
+
+    let info = Box::leak(Box::new(AccountInfo::new(
+        key, false, true, lamports, data, owner, false, 0,
+    )));
+
+    Account::try_from(info).unwrap()
+}
+
+#[test]
+fn native_sol_settles_through_the_temporary_account() {
+    let wrapped_sol = token_program_account::<Mint>(native_mint::ID, true);
+    let other_mint = token_program_account::<Mint>(Pubkey::new_unique(), true);
+
+    let mut native_sol_account = token_program_account::<TokenAccount>(native_mint::ID, false);
+    let mut user_token_account = token_program_account::<TokenAccount>(other_mint.key(), false);
+    let native_sol_key = native_sol_account.key();
+    let user_key = user_token_account.key();
+
+    // Without native SOL every leg settles through the user's token accounts
+    let (one, two) = native_sol_legs(false, Some(&mut native_sol_account), &wrapped_sol).unwrap();
+    assert!(one.is_none() && two.is_none());
+
+    // The temporary account goes to whichever leg is wrapped SOL
+    let (one, two) = native_sol_legs(true, Some(&mut native_sol_account), &wrapped_sol).unwrap();
+    assert!(one.is_some() && two.is_none());
+    assert_eq!(
+        settlement_account(one, Some(&mut user_token_account))
+            .unwrap()
+            .key(),
+        native_sol_key
+    );
+
+    let (one, two) = native_sol_legs(true, Some(&mut native_sol_account), &other_mint).unwrap();
+    assert!(two.is_some());
+    assert_eq!(
+        settlement_account(one, Some(&mut user_token_account))
+            .unwrap()
+            .key(),
+        user_key
+    );
+
+    // Paying in native SOL needs the temporary account, any other leg a token account
+    assert!(native_sol_legs(true, None, &wrapped_sol).is_err());
+    assert!(settlement_account(None, None).is_err());
+}
@@ -0,0 +1,63 @@
+use crate::state::EscrowedOrder;
+use anchor_lang::prelude::*;
+
+// A stop-loss or take-profit order selling `amount_in` of the input mint once the
+// pool TWAP, in output tokens per input token, crosses `trigger_price`.
+#[account]
+pub struct TriggerOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub order_id: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub trigger_price: f64,
+    // Sells once the price rises to the trigger, otherwise once it falls to it
+    pub is_take_profit: bool,
+    pub min_amount_out: u64,
+    pub bump: u8,
+}
+
+impl TriggerOrder {
+    pub const SEED_PREFIX: &'static str = "trigger_order";
+
+    pub const ESCROW_SEED_PREFIX: &'static str = "trigger_order_escrow";
+
+    // Seconds the trigger price is averaged over, a single block cannot move it
+    pub const TWAP_WINDOW: i64 = 5 * 60;
+
+    // Discriminator (8) + owner (32) + pool (32) + order id (8) + input mint (32)
+    // + output mint (32) + amount in (8) + trigger price (8) + is take profit (1)
+    // + min amount out (8) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 32 + 8 + 8 + 1 + 8 + 1;
+
+    pub fn is_triggered(&self, price: f64) -> bool {
+        if self.is_take_profit {
+            price >= self.trigger_price
+        } else {
+            price <= self.trigger_price
+        }
+    }
+}
+
+impl EscrowedOrder for TriggerOrder {
+    fn seed_prefix() -> &'static str {
+        Self::SEED_PREFIX
+    }
+
+    fn pool_key(&self) -> Pubkey {
+        self.pool
+    }
+
+    fn owner_key(&self) -> Pubkey {
+        self.owner
+    }
+
+    fn id(&self) -> u64 {
+        self.order_id
+    }
+
+    fn bump(&self) -> u8 {
+        self.bump
+    }
+}
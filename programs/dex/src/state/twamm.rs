@@ -0,0 +1,404 @@
+use crate::errors::DexProgramError;
+use crate::state::{DexConfiguration, LiquidityPool, PAUSE_SWAPS};
+use anchor_lang::prelude::*;
+
+// Long-term orders sell a fixed amount every interval. All orders selling the
+// same token are aggregated into a virtual order pool, which trades lazily at
+// every interval boundary the next time the pool is touched.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct VirtualOrderPool {
+    pub sell_rate: u64,
+    pub earnings_per_sell_rate: u128,
+}
+
+impl VirtualOrderPool {
+    // Sell rate (8) + earnings per sell rate (16)
+    pub const SIZE: usize = 8 + 16;
+}
+
+// Orders expiring at a boundary and the earnings accumulators at that boundary.
+// A slot is only reused once every order expiring in it has been settled.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct TwammBoundary {
+    pub interval: u64,
+    pub expiring_sell_rates: [u64; 2],
+    pub earnings_per_sell_rate: [u128; 2],
+    pub open_orders: u32,
+}
+
+impl TwammBoundary {
+    // Interval (8) + expiring sell rates (8 * 2) + earnings per sell rate (16 * 2)
+    // + open orders (4)
+    pub const SIZE: usize = 8 + 8 * 2 + 16 * 2 + 4;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug)]
+pub struct Twamm {
+    pub last_executed_interval: u64,
+    // Indexed by the token being sold, 0 sells token one and 1 sells token two
+    pub order_pools: [VirtualOrderPool; 2],
+    // Unsold input and unclaimed proceeds held in the pool vaults, per token
+    pub balances: [u64; 2],
+    pub boundaries: [TwammBoundary; Twamm::BOUNDARY_SLOTS],
+}
+
+impl Twamm {
+    // Length of an interval in seconds
+    pub const INTERVAL: i64 = 60 * 60;
+
+    // Longest long-term order, in intervals
+    pub const MAX_ORDER_INTERVALS: u64 = 24;
+
+    pub const BOUNDARY_SLOTS: usize = 32;
+
+    // Intervals traded by a single execution. A pool that missed more, because it
+    // had no liquidity or swaps were paused, catches up over several transactions
+    // instead of running one out of compute.
+    pub const MAX_INTERVALS_PER_EXECUTION: u64 = 8;
+
+    // Last executed interval (8) + order pools (24 * 2) + balances (8 * 2)
+    // + boundaries (60 * 32)
+    pub const SIZE: usize =
+        8 + VirtualOrderPool::SIZE * 2 + 8 * 2 + TwammBoundary::SIZE * Self::BOUNDARY_SLOTS;
+
+    pub fn interval_at(now: i64) -> u64 {
+        (now.max(0) / Self::INTERVAL) as u64
+    }
+
+    pub fn boundary(&self, interval: u64) -> &TwammBoundary {
+        &self.boundaries[(interval % Self::BOUNDARY_SLOTS as u64) as usize]
+    }
+
+    pub fn boundary_mut(&mut self, interval: u64) -> &mut TwammBoundary {
+        &mut self.boundaries[(interval % Self::BOUNDARY_SLOTS as u64) as usize]
+    }
+
+    // Earnings per sell rate an order selling `side` has accrued up to now, or up
+    // to its expiry once that has passed.
+    pub fn earnings_per_sell_rate(&self, side: usize, expiry_interval: u64) -> u128 {
+        if self.last_executed_interval >= expiry_interval {
+            self.boundary(expiry_interval).earnings_per_sell_rate[side]
+        } else {
+            self.order_pools[side].earnings_per_sell_rate
+        }
+    }
+
+    pub fn add_order(&mut self, side: usize, sell_rate: u64, expiry_interval: u64) -> Result<()> {
+        let boundary = self.boundary_mut(expiry_interval);
+
+        if boundary.interval != expiry_interval {
+            if boundary.open_orders > 0 {
+                return err!(DexProgramError::TwammBoundaryInUse);
+            }
+
+            *boundary = TwammBoundary {
+                interval: expiry_interval,
+                ..TwammBoundary::default()
+            };
+        }
+
+        boundary.expiring_sell_rates[side] = boundary.expiring_sell_rates[side]
+            .checked_add(sell_rate)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        boundary.open_orders += 1;
+
+        self.order_pools[side].sell_rate = self.order_pools[side]
+            .sell_rate
+            .checked_add(sell_rate)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    pub fn remove_order(
+        &mut self,
+        side: usize,
+        sell_rate: u64,
+        expiry_interval: u64,
+    ) -> Result<()> {
+        // Orders that already expired stopped selling at their boundary
+        if self.last_executed_interval < expiry_interval {
+            self.order_pools[side].sell_rate = self.order_pools[side]
+                .sell_rate
+                .checked_sub(sell_rate)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+            let boundary = self.boundary_mut(expiry_interval);
+            boundary.expiring_sell_rates[side] = boundary.expiring_sell_rates[side]
+                .checked_sub(sell_rate)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        }
+
+        let boundary = self.boundary_mut(expiry_interval);
+        boundary.open_orders = boundary.open_orders.saturating_sub(1);
+
+        Ok(())
+    }
+
+    pub fn has_open_orders(&self) -> bool {
+        self.boundaries
+            .iter()
+            .any(|boundary| boundary.open_orders > 0)
+    }
+
+    pub fn credit(&mut self, token: usize, amount: u64) -> Result<()> {
+        self.balances[token] = self.balances[token]
+            .checked_add(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+
+    pub fn debit(&mut self, token: usize, amount: u64) -> Result<()> {
+        self.balances[token] = self.balances[token]
+            .checked_sub(amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        Ok(())
+    }
+}
+
+// A long-term order selling `sell_rate` of the input mint at every interval
+// boundary until `expiry_interval`.
+#[account]
+pub struct LongTermOrder {
+    pub owner: Pubkey,
+    pub pool: Pubkey,
+    pub order_id: u64,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub sell_rate: u64,
+    pub expiry_interval: u64,
+    pub earnings_checkpoint: u128,
+    pub bump: u8,
+}
+
+impl LongTermOrder {
+    pub const SEED_PREFIX: &'static str = "long_term_order";
+
+    // Discriminator (8) + owner (32) + pool (32) + order id (8) + input mint (32)
+    // + output mint (32) + sell rate (8) + expiry interval (8)
+    // + earnings checkpoint (16) + bump (1)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 32 + 8 + 8 + 16 + 1;
+
+    // Proceeds accrued since the last withdrawal, moving the checkpoint forward
+    pub fn take_proceeds(&mut self, twamm: &Twamm, side: usize) -> Result<u64> {
+        let earnings_per_sell_rate = twamm.earnings_per_sell_rate(side, self.expiry_interval);
+        let proceeds = (self.sell_rate as u128)
+            .checked_mul(earnings_per_sell_rate.wrapping_sub(self.earnings_checkpoint))
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            >> LiquidityPool::FEE_GROWTH_SHIFT;
+
+        self.earnings_checkpoint = earnings_per_sell_rate;
+
+        Ok(proceeds as u64)
+    }
+
+    // Input that has not been sold yet
+    pub fn unsold_amount(&self, twamm: &Twamm) -> Result<u64> {
+        let remaining_intervals = self
+            .expiry_interval
+            .saturating_sub(twamm.last_executed_interval);
+
+        Ok(self
+            .sell_rate
+            .checked_mul(remaining_intervals)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?)
+    }
+
+    pub fn is_expired(&self, twamm: &Twamm) -> bool {
+        twamm.last_executed_interval >= self.expiry_interval
+    }
+}
+
+impl LiquidityPool {
+    // Settles the interval boundaries passed since the pool was last touched, up
+    // to `MAX_INTERVALS_PER_EXECUTION` of them. Opposing flows are matched against each other at the spot price and only
+    // the remainder trades against the curve.
+    pub fn execute_virtual_orders(
+        &mut self,
+        dex_configuration: &DexConfiguration,
+        now: i64,
+    ) -> Result<()> {
+        // Virtual trades move the price, the oracle has to see the price before
+        self.observe(now);
+
+        // Long-term orders are swaps too. While swaps are paused the intervals are
+        // left unexecuted and trade once swaps resume.
+        if self.is_paused(dex_configuration, PAUSE_SWAPS) {
+            return Ok(());
+        }
+
+        let current_interval = Twamm::interval_at(now);
+        let fees = self.current_fee(dex_configuration, now);
+        let mut executed_intervals = 0_u64;
+
+        while self.twamm.last_executed_interval < current_interval {
+            let sell_one = self.twamm.order_pools[0].sell_rate;
+            let sell_two = self.twamm.order_pools[1].sell_rate;
+
+            // Without active orders nothing expires either, skip straight ahead
+            if sell_one == 0 && sell_two == 0 {
+                self.twamm.last_executed_interval = current_interval;
+                break;
+            }
+
+            // Without liquidity there is no price to trade at. The orders wait and
+            // trade the intervals they missed once liquidity is back.
+            if self.reserve_one == 0 || self.reserve_two == 0 {
+                break;
+            }
+
+            if executed_intervals == Twamm::MAX_INTERVALS_PER_EXECUTION {
+                break;
+            }
+
+            let interval = self.twamm.last_executed_interval + 1;
+            let (earned_one, earned_two) = self.execute_virtual_trades(fees, sell_one, sell_two)?;
+
+            for (side, (sell_rate, earned)) in [(sell_one, earned_two), (sell_two, earned_one)]
+                .into_iter()
+                .enumerate()
+            {
+                if sell_rate > 0 {
+                    let growth =
+                        ((earned as u128) << LiquidityPool::FEE_GROWTH_SHIFT) / sell_rate as u128;
+                    self.twamm.order_pools[side].earnings_per_sell_rate = self.twamm.order_pools
+                        [side]
+                        .earnings_per_sell_rate
+                        .wrapping_add(growth);
+                }
+            }
+
+            let earnings_per_sell_rate = [
+                self.twamm.order_pools[0].earnings_per_sell_rate,
+                self.twamm.order_pools[1].earnings_per_sell_rate,
+            ];
+
+            let boundary = *self.twamm.boundary(interval);
+
+            if boundary.interval == interval {
+                for side in 0..2 {
+                    self.twamm.order_pools[side].sell_rate = self.twamm.order_pools[side]
+                        .sell_rate
+                        .checked_sub(boundary.expiring_sell_rates[side])
+                        .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+                }
+
+                self.twamm.boundary_mut(interval).earnings_per_sell_rate = earnings_per_sell_rate;
+            }
+
+            self.twamm.last_executed_interval = interval;
+            executed_intervals += 1;
+        }
+
+        Ok(())
+    }
+
+    // Rounding leaves a little of every execution in the long-term order balances.
+    // Once the last order is gone nobody can claim it and it joins the reserves.
+    pub fn sweep_twamm_dust(&mut self) -> Result<()> {
+        if self.twamm.has_open_orders() {
+            return Ok(());
+        }
+
+        self.reserve_one = self
+            .reserve_one
+            .checked_add(self.twamm.balances[0])
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.reserve_two = self
+            .reserve_two
+            .checked_add(self.twamm.balances[1])
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        self.twamm.balances = [0_u64; 2];
+
+        Ok(())
+    }
+
+    // Trades one interval's worth of both virtual order pools, returning the
+    // amounts of token one and token two earned by the opposite sides.
+    fn execute_virtual_trades(
+        &mut self,
+        fees: f64,
+        sell_one: u64,
+        sell_two: u64,
+    ) -> Result<(u64, u64)> {
+        let (reserve_one, reserve_two) = (self.reserve_one as u128, self.reserve_two as u128);
+
+        // Value of token one being sold expressed in token two
+        let sell_one_value = (sell_one as u128)
+            .checked_mul(reserve_two)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            / reserve_one;
+
+        let (earned_one, earned_two) = if sell_one_value >= sell_two as u128 {
+            let matched_one = ((sell_two as u128)
+                .checked_mul(reserve_one)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+                / reserve_two) as u64;
+            let amount_out = self.execute_virtual_swap(fees, true, sell_one - matched_one)?;
+
+            (
+                matched_one,
+                sell_two
+                    .checked_add(amount_out)
+                    .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?,
+            )
+        } else {
+            let matched_two = sell_one_value as u64;
+            let amount_out = self.execute_virtual_swap(fees, false, sell_two - matched_two)?;
+
+            (
+                sell_one
+                    .checked_add(amount_out)
+                    .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?,
+                matched_two,
+            )
+        };
+
+        Ok((earned_one, earned_two))
+    }
+
+    fn execute_virtual_swap(
+        &mut self,
+        fees: f64,
+        is_token_one_in: bool,
+        amount: u64,
+    ) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0_u64);
+        }
+
+        // Virtual orders only move balances inside the vaults, the fee adjustment
+        // is scale independent so the amounts are quoted in base units.
+        let swap_amounts = self.quote_swap_exact_in(fees, is_token_one_in, 0, amount)?;
+
+        let (token_in, token_out) = if is_token_one_in { (0, 1) } else { (1, 0) };
+
+        let (reserve_in, reserve_out) = if is_token_one_in {
+            (self.reserve_one, self.reserve_two)
+        } else {
+            (self.reserve_two, self.reserve_one)
+        };
+
+        let new_reserve_in = reserve_in
+            .checked_add(amount - swap_amounts.fee_amount)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+        let new_reserve_out = reserve_out
+            .checked_sub(swap_amounts.amount_out)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?;
+
+        if is_token_one_in {
+            (self.reserve_one, self.reserve_two) = (new_reserve_in, new_reserve_out);
+        } else {
+            (self.reserve_one, self.reserve_two) = (new_reserve_out, new_reserve_in);
+        }
+
+        self.collect_fee(is_token_one_in, swap_amounts.fee_amount)?;
+
+        self.twamm.debit(token_in, amount)?;
+        self.twamm.credit(token_out, swap_amounts.amount_out)?;
+
+        Ok(swap_amounts.amount_out)
+    }
+}
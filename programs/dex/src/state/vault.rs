@@ -0,0 +1,165 @@
+use crate::{
+    errors::DexProgramError,
+    state::{LiquidityPool, LiquidityProvider},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
+
+// Holds a liquidity position on behalf of its depositors, who own it through the
+// vault's share token. Harvesting reinvests the position's earnings into it.
+#[account]
+pub struct CompoundingVault {
+    pub pool: Pubkey,
+    pub share_mint: Pubkey,
+    pub bump: u8,
+    pub reserved: [u8; 32],
+}
+
+impl CompoundingVault {
+    pub const SEED_PREFIX: &'static str = "compounding_vault";
+
+    pub const SHARE_MINT_SEED_PREFIX: &'static str = "vault_share_mint";
+
+    pub const SHARE_DECIMALS: u8 = 9;
+
+    // Vault shares that are never minted but always count as outstanding. The
+    // first deposit pays for them, and inflating the share price by donating to
+    // the vault hands most of the donation to them.
+    pub const DEAD_SHARES: u64 = 1_000;
+
+    // Discriminator (8) + pool (32) + share mint (32) + bump (1) + reserved (32)
+    pub const ACCOUNT_SIZE: usize = 8 + 32 + 32 + 1 + 32;
+
+    pub fn new(pool: Pubkey, share_mint: Pubkey, bump: u8) -> Self {
+        Self {
+            pool,
+            share_mint,
+            bump,
+            reserved: [0_u8; 32],
+        }
+    }
+
+    // What the vault's position is worth in liquidity shares, including the
+    // earnings that have not been harvested yet. The vault's fees and rewards have
+    // to be accrued first.
+    pub fn liquidity_shares(
+        pool: &LiquidityPool,
+        vault_liquidity_provider: &LiquidityProvider,
+    ) -> Result<u64> {
+        vault_liquidity_provider
+            .shares
+            .checked_add(Self::pending_shares(pool, vault_liquidity_provider)?)
+            .ok_or(error!(DexProgramError::OverflowOrUnderflowOccurred))
+    }
+
+    // Once every depositor has left, whatever the position still holds belongs
+    // to the dead shares and the next deposit buys in at that price.
+    pub fn vault_shares_for_deposit(
+        shares: u64,
+        vault_liquidity_shares: u64,
+        vault_share_supply: u64,
+    ) -> Result<u64> {
+        if vault_liquidity_shares == 0 {
+            return Ok(shares.saturating_sub(Self::DEAD_SHARES));
+        }
+
+        Ok(((shares as u128)
+            .checked_mul(vault_share_supply as u128 + Self::DEAD_SHARES as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            .checked_div(vault_liquidity_shares as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?) as u64)
+    }
+
+    // Fees and pool token rewards the vault has earned but not compounded yet,
+    // valued in liquidity shares at the pool price. Each share is worth its part
+    // of both reserves, so a single token amount is worth half as many shares as
+    // it would be against its reserve alone.
+    pub fn pending_shares(
+        pool: &LiquidityPool,
+        vault_liquidity_provider: &LiquidityProvider,
+    ) -> Result<u64> {
+        let mut pending_one = vault_liquidity_provider.fees_owed_one as u128;
+        let mut pending_two = vault_liquidity_provider.fees_owed_two as u128;
+
+        for (reward_vault, rewards_owed) in pool
+            .rewards
+            .iter()
+            .zip(vault_liquidity_provider.rewards_owed)
+        {
+            if reward_vault.mint == pool.token_one {
+                pending_one += rewards_owed as u128;
+            } else if reward_vault.mint == pool.token_two {
+                pending_two += rewards_owed as u128;
+            }
+        }
+
+        let shares_for = |pending: u128, reserve: u64| -> Result<u128> {
+            if reserve == 0 {
+                return Ok(0_u128);
+            }
+
+            Ok(pending
+                .checked_mul(pool.total_supply as u128)
+                .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+                / reserve as u128)
+        };
+
+        let pending_shares = (shares_for(pending_one, pool.reserve_one)?
+            + shares_for(pending_two, pool.reserve_two)?)
+            / 2;
+
+        u64::try_from(pending_shares)
+            .map_err(|_| error!(DexProgramError::OverflowOrUnderflowOccurred))
+    }
+
+    pub fn shares_for_withdrawal(
+        vault_shares: u64,
+        vault_liquidity_shares: u64,
+        vault_share_supply: u64,
+    ) -> Result<u64> {
+        Ok(((vault_shares as u128)
+            .checked_mul(vault_liquidity_shares as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?
+            .checked_div(vault_share_supply as u128 + Self::DEAD_SHARES as u128)
+            .ok_or(DexProgramError::OverflowOrUnderflowOccurred)?) as u64)
+    }
+}
+
+pub trait CompoundingVaultAccount<'info> {
+    fn mint_vault_shares(
+        &self,
+        share_mint: &Account<'info, Mint>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()>;
+}
+
+impl<'info> CompoundingVaultAccount<'info> for Account<'info, CompoundingVault> {
+    fn mint_vault_shares(
+        &self,
+        share_mint: &Account<'info, Mint>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+        token_program: &Program<'info, Token>,
+    ) -> Result<()> {
+        token::mint_to(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                token::MintTo {
+                    mint: share_mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.to_account_info(),
+                },
+                &[&[
+                    CompoundingVault::SEED_PREFIX.as_bytes(),
+                    self.pool.as_ref(),
+                    &[self.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+}
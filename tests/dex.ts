@@ -1,25 +1,2745 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
-import { Dex } from "../target/types/dex";
+import { BN, Program } from "@coral-xyz/anchor";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
+  TOKEN_PROGRAM_ID,
+  approve,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+} from "@solana/spl-token";
+import {
+  Ed25519Program,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SystemProgram,
+} from "@solana/web3.js";
 import { expect } from "chai";
+import { Dex } from "../target/types/dex";
+
+const DECIMALS = 6;
+const UNIT = 10 ** DECIMALS;
+
+const PAUSE_SWAPS = 1 << 0;
+const PAUSE_DEPOSITS = 1 << 1;
+const PAUSE_WITHDRAWALS = 1 << 2;
+
+type PoolFixture = {
+  mintOne: PublicKey;
+  mintTwo: PublicKey;
+  pool: PublicKey;
+  poolTokenAccountOne: PublicKey;
+  poolTokenAccountTwo: PublicKey;
+};
 
 describe("dex", () => {
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.Dex as Program<Dex>;
+  const connection = provider.connection;
+  const admin = (provider.wallet as anchor.Wallet).payer;
+  const user = Keypair.generate();
+
+  const [dexConfiguration] = PublicKey.findProgramAddressSync(
+    [Buffer.from("DexConfiguration")],
+    program.programId
+  );
+
+  // Pools are seeded with both mints, the greater key first
+  const findPool = (mintOne: PublicKey, mintTwo: PublicKey) => {
+    const seed =
+      Buffer.compare(mintOne.toBuffer(), mintTwo.toBuffer()) > 0
+        ? mintOne.toBase58() + mintTwo.toBase58()
+        : mintTwo.toBase58() + mintOne.toBase58();
 
-  it("Liquidity pool created", async () => {
-    
-    const [poolPda] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("liquidity_pool")],
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("liquidity_pool"), Buffer.from(seed)],
       program.programId
-    )
+    )[0];
+  };
+
+  const pda = (...seeds: (Buffer | Uint8Array)[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+
+  const findLiquidityProvider = (pool: PublicKey, owner: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("LiqudityProvider"), pool.toBuffer(), owner.toBuffer()],
+      program.programId
+    )[0];
+
+  const ata = (mint: PublicKey, owner: PublicKey) =>
+    getAssociatedTokenAddressSync(mint, owner, true);
+
+  const balance = async (address: PublicKey) =>
+    Number((await getAccount(connection, address)).amount);
+
+  // A stray transfer from the admin, escrows must give it back to their owner
+  const donate = (mint: PublicKey, to: PublicKey, amount: number) =>
+    transfer(connection, admin, ata(mint, admin.publicKey), to, admin, amount);
+
+  const airdrop = async (address: PublicKey) => {
+    const signature = await connection.requestAirdrop(
+      address,
+      10 * LAMPORTS_PER_SOL
+    );
+    await connection.confirmTransaction(signature);
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (error) {
+      expect(error.error.errorCode.code).to.equal(code);
+      return;
+    }
+
+    expect.fail(`expected ${code}`);
+  };
+
+  // The same pool seen from its second token, swaps then sell token two
+  const flip = (fixture: PoolFixture): PoolFixture => ({
+    mintOne: fixture.mintTwo,
+    mintTwo: fixture.mintOne,
+    pool: fixture.pool,
+    poolTokenAccountOne: fixture.poolTokenAccountTwo,
+    poolTokenAccountTwo: fixture.poolTokenAccountOne,
+  });
+
+  const fundWallet = async (fixture: PoolFixture, owner: PublicKey) => {
+    for (const mint of [fixture.mintOne, fixture.mintTwo]) {
+      // Wrapped SOL is paid in lamports, it cannot be minted
+      if (mint.equals(NATIVE_MINT)) {
+        continue;
+      }
+
+      const tokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        admin,
+        mint,
+        owner,
+        true
+      );
+
+      await mintTo(
+        connection,
+        admin,
+        mint,
+        tokenAccount.address,
+        admin,
+        1_000_000 * UNIT
+      );
+    }
+  };
+
+  const createTestMint = () =>
+    createMint(connection, admin, admin.publicKey, null, DECIMALS);
+
+  const createPool = async (
+    mintOne?: PublicKey,
+    mintTwo?: PublicKey
+  ): Promise<PoolFixture> => {
+    mintOne = mintOne ?? (await createTestMint());
+    mintTwo = mintTwo ?? (await createTestMint());
+    const pool = findPool(mintOne, mintTwo);
+
+    const fixture = {
+      mintOne,
+      mintTwo,
+      pool,
+      poolTokenAccountOne: ata(mintOne, pool),
+      poolTokenAccountTwo: ata(mintTwo, pool),
+    };
+
+    await program.methods
+      .createLiquidityPool()
+      .accounts({
+        pool,
+        mintTokenOne: mintOne,
+        mintTokenTwo: mintTwo,
+        poolTokenAccountOne: fixture.poolTokenAccountOne,
+        poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+        payer: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await fundWallet(fixture, admin.publicKey);
+    await fundWallet(fixture, user.publicKey);
+
+    return fixture;
+  };
+
+  // Accounts the legs of a trade settle through. A leg paid in native SOL goes
+  // through the signer's temporary wrapped SOL account, not a token account.
+  const settlementAccounts = (
+    fixture: PoolFixture,
+    owner: PublicKey,
+    signer: PublicKey,
+    nativeSol: boolean
+  ) => {
+    const isNative = (mint: PublicKey) => nativeSol && mint.equals(NATIVE_MINT);
+
+    return {
+      userTokenAccountOne: isNative(fixture.mintOne)
+        ? null
+        : ata(fixture.mintOne, owner),
+      userTokenAccountTwo: isNative(fixture.mintTwo)
+        ? null
+        : ata(fixture.mintTwo, owner),
+      nativeSolAccount: nativeSol
+        ? pda(Buffer.from("native_sol"), signer.toBuffer())
+        : null,
+      nativeMint: nativeSol ? NATIVE_MINT : null,
+    };
+  };
+
+  const addLiquidity = (
+    fixture: PoolFixture,
+    signer: Keypair,
+    amountOne: number,
+    amountTwo: number,
+    owner: PublicKey = signer.publicKey,
+    nativeSol = false
+  ) =>
+    program.methods
+      .addLiquidity(new BN(amountOne), new BN(amountTwo), nativeSol)
+      .accounts({
+        dexConfigurationAccount: dexConfiguration,
+        pool: fixture.pool,
+        liquidityProviderAccount: findLiquidityProvider(fixture.pool, owner),
+        mintTokenOne: fixture.mintOne,
+        mintTokenTwo: fixture.mintTwo,
+        poolTokenAccountOne: fixture.poolTokenAccountOne,
+        poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+        ...settlementAccounts(
+          fixture,
+          signer.publicKey,
+          signer.publicKey,
+          nativeSol
+        ),
+        owner,
+        user: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([signer])
+      .rpc();
+
+  const removeLiquidity = (
+    fixture: PoolFixture,
+    signer: Keypair,
+    shares: number,
+    owner: PublicKey = signer.publicKey,
+    nativeSol = false
+  ) =>
+    program.methods
+      .removeLiquidity(new BN(shares), nativeSol)
+      .accounts({
+        dexConfigurationAccount: dexConfiguration,
+        pool: fixture.pool,
+        liquidityProviderAccount: findLiquidityProvider(fixture.pool, owner),
+        mintTokenOne: fixture.mintOne,
+        mintTokenTwo: fixture.mintTwo,
+        poolTokenAccountOne: fixture.poolTokenAccountOne,
+        poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+        ...settlementAccounts(fixture, owner, signer.publicKey, nativeSol),
+        owner,
+        user: signer.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      })
+      .signers([signer])
+      .rpc();
+
+  const swapAccounts = (
+    fixture: PoolFixture,
+    signer: Keypair,
+    nativeSol = false
+  ) => ({
+    dexConfigurationAccount: dexConfiguration,
+    pool: fixture.pool,
+    mintTokenOne: fixture.mintOne,
+    mintTokenTwo: fixture.mintTwo,
+    poolTokenAccountOne: fixture.poolTokenAccountOne,
+    poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+    ...settlementAccounts(
+      fixture,
+      signer.publicKey,
+      signer.publicKey,
+      nativeSol
+    ),
+    recipientTokenAccount: null,
+    referrerTokenAccount: null,
+    referrerStats: null,
+    stakeAccount: null,
+    user: signer.publicKey,
+    systemProgram: SystemProgram.programId,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+  });
+
+  const swap = (
+    fixture: PoolFixture,
+    signer: Keypair,
+    amount: number,
+    nativeSol = false
+  ) =>
+    program.methods
+      .swap(new BN(amount), nativeSol, null, 0)
+      .accounts(swapAccounts(fixture, signer, nativeSol))
+      .signers([signer])
+      .rpc();
+
+  const quoteAccounts = (fixture: PoolFixture) => ({
+    dexConfigurationAccount: dexConfiguration,
+    pool: fixture.pool,
+    mintTokenOne: fixture.mintOne,
+    mintTokenTwo: fixture.mintTwo,
+    stakeAccount: null,
+  });
+
+  // Decodes what an instruction hands back through return data
+  const returnData = async (
+    builder: { simulate: () => Promise<{ raw: readonly string[] }> },
+    type: string
+  ) => {
+    const prefix = `Program return: ${program.programId} `;
+    const { raw } = await builder.simulate();
+    const log = raw.find((line) => line.startsWith(prefix));
+
+    return program.coder.types.decode(
+      type,
+      Buffer.from(log.slice(prefix.length), "base64")
+    );
+  };
+
+  let fixture: PoolFixture;
+
+  before(async () => {
+    await airdrop(user.publicKey);
+
+    await program.methods
+      .initializeDex(0.3)
+      .accounts({
+        dexConfigurationAccount: dexConfiguration,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    fixture = await createPool();
+  });
+
+  describe("liquidity pool", () => {
+    it("creates a pool for a pair of mints", async () => {
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+
+      expect(pool.tokenOne.equals(fixture.mintOne)).to.be.true;
+      expect(pool.tokenTwo.equals(fixture.mintTwo)).to.be.true;
+      expect(pool.totalSupply.toNumber()).to.equal(0);
+    });
+
+    it("adds and removes liquidity", async () => {
+      await addLiquidity(fixture, admin, 1_000 * UNIT, 1_000 * UNIT);
+
+      let pool = await program.account.liquidityPool.fetch(fixture.pool);
+      const liquidityProvider = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(fixture.pool, admin.publicKey)
+      );
+
+      expect(pool.reserveOne.toNumber()).to.equal(1_000 * UNIT);
+      expect(pool.reserveTwo.toNumber()).to.equal(1_000 * UNIT);
+      const shares = liquidityProvider.shares.toNumber();
+      expect(shares).to.equal(pool.totalSupply.toNumber());
+
+      await removeLiquidity(fixture, admin, shares / 10);
+
+      pool = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(pool.reserveOne.toNumber()).to.equal(900 * UNIT);
+
+      await addLiquidity(fixture, admin, 100 * UNIT, 100 * UNIT);
+    });
+
+    it("swaps against the reserves", async () => {
+      const before = await balance(ata(fixture.mintTwo, user.publicKey));
+
+      await swap(fixture, user, UNIT);
+
+      const after = await balance(ata(fixture.mintTwo, user.publicKey));
+      expect(after).to.be.greaterThan(before);
+
+      // Selling token two moves the reserves the other way
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      await swap(flip(fixture), user, after - before);
+
+      const flipped = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(flipped.reserveOne.lt(pool.reserveOne)).to.be.true;
+      expect(flipped.reserveTwo.gt(pool.reserveTwo)).to.be.true;
+    });
+  });
+
+  describe("dynamic fees", () => {
+    const configureDynamicFee = (
+      signer: Keypair,
+      enabled: boolean,
+      minFee: number,
+      maxFee: number
+    ) =>
+      program.methods
+        .configureDynamicFee(enabled, minFee, maxFee)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("only lets the admin configure the dynamic fee", async () => {
+      await expectError(
+        configureDynamicFee(user, true, 0.1, 1),
+        "Unauthorized"
+      );
+      await expectError(configureDynamicFee(admin, true, 1, 0.1), "InvalidFee");
+    });
+
+    it("raises the fee with volatility", async () => {
+      await configureDynamicFee(admin, true, 0.1, 1);
+
+      // Swapping there and back leaves only the fees behind
+      const roundTripLoss = async () => {
+        const tokenOne = ata(fixture.mintOne, user.publicKey);
+        const tokenTwo = ata(fixture.mintTwo, user.publicKey);
+        const startOne = await balance(tokenOne);
+        const startTwo = await balance(tokenTwo);
+
+        await swap(fixture, user, 10 * UNIT);
+        await swap(flip(fixture), user, (await balance(tokenTwo)) - startTwo);
+
+        return startOne - (await balance(tokenOne));
+      };
+
+      const firstLoss = await roundTripLoss();
+
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(pool.volatilityAccumulator).to.be.greaterThan(0);
+
+      // The second round trip pays more than the first one for the same input
+      const secondLoss = await roundTripLoss();
+      expect(secondLoss).to.be.greaterThan(firstLoss);
+
+      await configureDynamicFee(admin, false, 0, 0);
+    });
+  });
+
+  describe("native SOL", () => {
+    let nativeFixture: PoolFixture;
+
+    before(async () => {
+      nativeFixture = await createPool(NATIVE_MINT);
+    });
+
+    it("adds liquidity straight from lamports", async () => {
+      const lamports = await connection.getBalance(user.publicKey);
+
+      await addLiquidity(
+        nativeFixture,
+        user,
+        LAMPORTS_PER_SOL,
+        1_000 * UNIT,
+        user.publicKey,
+        true
+      );
+
+      const pool = await program.account.liquidityPool.fetch(
+        nativeFixture.pool
+      );
+      expect(pool.reserveOne.toNumber()).to.equal(LAMPORTS_PER_SOL);
+      expect(await connection.getBalance(user.publicKey)).to.be.lessThan(
+        lamports - LAMPORTS_PER_SOL
+      );
+    });
+
+    it("swaps lamports in and out", async () => {
+      const userTokenAccount = ata(nativeFixture.mintTwo, user.publicKey);
+      const tokenBalance = await balance(userTokenAccount);
+
+      await swap(nativeFixture, user, LAMPORTS_PER_SOL / 10, true);
+
+      const received = (await balance(userTokenAccount)) - tokenBalance;
+      expect(received).to.be.greaterThan(0);
+
+      // The user never needed a wrapped SOL account, the temporary one is
+      // closed again
+      const wrapped = ata(NATIVE_MINT, user.publicKey);
+      expect(await connection.getAccountInfo(wrapped)).to.be.null;
+      const temporary = pda(
+        Buffer.from("native_sol"),
+        user.publicKey.toBuffer()
+      );
+      expect(await connection.getAccountInfo(temporary)).to.be.null;
+
+      const lamports = await connection.getBalance(user.publicKey);
+      await swap(flip(nativeFixture), user, received, true);
+
+      expect(await connection.getBalance(user.publicKey)).to.be.greaterThan(
+        lamports
+      );
+    });
+
+    it("removes liquidity as lamports", async () => {
+      const liquidityProvider = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(nativeFixture.pool, user.publicKey)
+      );
+      const lamports = await connection.getBalance(user.publicKey);
+
+      await removeLiquidity(
+        nativeFixture,
+        user,
+        liquidityProvider.shares.toNumber(),
+        user.publicKey,
+        true
+      );
+
+      expect(await connection.getBalance(user.publicKey)).to.be.greaterThan(
+        lamports
+      );
+    });
+
+    it("rejects native SOL on pools without wrapped SOL", async () => {
+      await expectError(
+        swap(fixture, user, UNIT, true),
+        "NativeSolNotSupported"
+      );
+    });
+  });
+
+  describe("quotes", () => {
+    it("quotes what a swap pays out", async () => {
+      const quote = await returnData(
+        program.methods
+          .quoteSwapExactIn(new BN(UNIT), 0)
+          .accounts(quoteAccounts(fixture)),
+        "SwapAmounts"
+      );
+
+      const userTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(userTokenAccount);
+      await swap(fixture, user, UNIT);
+
+      expect((await balance(userTokenAccount)) - before).to.equal(
+        quote.amountOut.toNumber()
+      );
+    });
+
+    it("quotes the input for an exact output", async () => {
+      const quote = await returnData(
+        program.methods
+          .quoteSwapExactOut(new BN(UNIT), 0)
+          .accounts(quoteAccounts(fixture)),
+        "SwapAmounts"
+      );
+
+      const userTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(userTokenAccount);
+      await swap(fixture, user, quote.amountIn.toNumber());
+
+      expect((await balance(userTokenAccount)) - before).to.be.at.least(UNIT);
+    });
+
+    it("quotes liquidity changes", async () => {
+      const added = await returnData(
+        program.methods
+          .quoteAddLiquidity(new BN(10 * UNIT), new BN(10 * UNIT))
+          .accounts(quoteAccounts(fixture)),
+        "LiquidityAmounts"
+      );
+      expect(added.shares.toNumber()).to.be.greaterThan(0);
+
+      const removed = await returnData(
+        program.methods
+          .quoteRemoveLiquidity(added.shares)
+          .accounts(quoteAccounts(fixture)),
+        "LiquidityAmounts"
+      );
+      expect(removed.amountOne.toNumber()).to.be.at.most(10 * UNIT);
+      expect(removed.amountTwo.toNumber()).to.be.greaterThan(0);
+    });
+  });
+
+  describe("returned amounts", () => {
+    it("returns the amounts of a swap", async () => {
+      const builder = program.methods
+        .swap(new BN(UNIT), false, null, 0)
+        .accounts(swapAccounts(fixture, user))
+        .signers([user]);
+
+      const swapAmounts = await returnData(builder, "SwapAmounts");
+
+      const userTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(userTokenAccount);
+      await builder.rpc();
+
+      expect(swapAmounts.amountIn.toNumber()).to.equal(UNIT);
+      expect((await balance(userTokenAccount)) - before).to.equal(
+        swapAmounts.amountOut.toNumber()
+      );
+    });
+
+    it("returns the shares minted by a deposit", async () => {
+      const liquidityProvider = findLiquidityProvider(
+        fixture.pool,
+        admin.publicKey
+      );
+      const { shares } = await program.account.liquidityProvider.fetch(
+        liquidityProvider
+      );
+
+      const builder = program.methods
+        .addLiquidity(new BN(10 * UNIT), new BN(10 * UNIT), false)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          liquidityProviderAccount: liquidityProvider,
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          userTokenAccountOne: ata(fixture.mintOne, admin.publicKey),
+          userTokenAccountTwo: ata(fixture.mintTwo, admin.publicKey),
+          nativeSolAccount: null,
+          nativeMint: null,
+          owner: admin.publicKey,
+          user: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        });
+
+      const liquidityAmounts = await returnData(builder, "LiquidityAmounts");
+      await builder.rpc();
+
+      const after = await program.account.liquidityProvider.fetch(
+        liquidityProvider
+      );
+      expect(after.shares.sub(shares).toNumber()).to.equal(
+        liquidityAmounts.shares.toNumber()
+      );
+    });
+  });
+
+  describe("recipient and deadline", () => {
+    it("pays the output to the recipient", async () => {
+      const recipientTokenAccount = ata(fixture.mintTwo, admin.publicKey);
+      const userTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const recipientBalance = await balance(recipientTokenAccount);
+      const userBalance = await balance(userTokenAccount);
+
+      await program.methods
+        .swap(new BN(UNIT), false, null, 0)
+        .accounts({ ...swapAccounts(fixture, user), recipientTokenAccount })
+        .signers([user])
+        .rpc();
+
+      expect(await balance(recipientTokenAccount)).to.be.greaterThan(
+        recipientBalance
+      );
+      expect(await balance(userTokenAccount)).to.equal(userBalance);
+    });
+
+    it("rejects swaps past their deadline", async () => {
+      const deadline = Math.floor(Date.now() / 1000) - 60;
+
+      await expectError(
+        program.methods
+          .swap(new BN(UNIT), false, new BN(deadline), 0)
+          .accounts(swapAccounts(fixture, user))
+          .signers([user])
+          .rpc(),
+        "DeadlineExceeded"
+      );
+    });
+  });
+
+  describe("pause controls", () => {
+    const guardian = Keypair.generate();
+
+    const pauseAccounts = (authority: Keypair) => ({
+      dexConfigurationAccount: dexConfiguration,
+      pool: fixture.pool,
+      authority: authority.publicKey,
+    });
+
+    it("lets the admin appoint a guardian", async () => {
+      await expectError(
+        program.methods
+          .setGuardian(guardian.publicKey)
+          .accounts({
+            dexConfigurationAccount: dexConfiguration,
+            admin: user.publicKey,
+          })
+          .signers([user])
+          .rpc(),
+        "Unauthorized"
+      );
+
+      await program.methods
+        .setGuardian(guardian.publicKey)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          admin: admin.publicKey,
+        })
+        .rpc();
+
+      const config = await program.account.dexConfiguration.fetch(
+        dexConfiguration
+      );
+      expect(config.guardian.equals(guardian.publicKey)).to.be.true;
+    });
+
+    it("halts swaps on a pool until the admin resumes them", async () => {
+      await program.methods
+        .pausePool(PAUSE_SWAPS)
+        .accounts(pauseAccounts(guardian))
+        .signers([guardian])
+        .rpc();
+
+      await expectError(swap(fixture, user, UNIT), "OperationPaused");
+
+      await expectError(
+        program.methods
+          .unpausePool(PAUSE_SWAPS)
+          .accounts(pauseAccounts(guardian))
+          .signers([guardian])
+          .rpc(),
+        "Unauthorized"
+      );
+
+      await program.methods
+        .unpausePool(PAUSE_SWAPS)
+        .accounts(pauseAccounts(admin))
+        .rpc();
+
+      await swap(fixture, user, UNIT);
+    });
+
+    it("halts deposits across the dex", async () => {
+      await program.methods
+        .pauseDex(PAUSE_DEPOSITS)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          authority: guardian.publicKey,
+        })
+        .signers([guardian])
+        .rpc();
+
+      await expectError(
+        addLiquidity(fixture, user, UNIT, UNIT),
+        "OperationPaused"
+      );
+
+      await program.methods
+        .unpauseDex(PAUSE_DEPOSITS)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          authority: admin.publicKey,
+        })
+        .rpc();
+    });
+
+    it("keeps withdrawals out of the guardian's reach", async () => {
+      await expectError(
+        program.methods
+          .pausePool(PAUSE_WITHDRAWALS)
+          .accounts(pauseAccounts(guardian))
+          .signers([guardian])
+          .rpc(),
+        "Unauthorized"
+      );
+
+      await expectError(
+        program.methods
+          .pausePool(1 << 3)
+          .accounts(pauseAccounts(admin))
+          .rpc(),
+        "InvalidPauseFlags"
+      );
+    });
+  });
+
+  describe("circuit breaker", () => {
+    const configureCircuitBreaker = (
+      signer: Keypair,
+      maxSlotMovementBps: number,
+      maxWindowMovementBps: number,
+      windowSlots: number
+    ) =>
+      program.methods
+        .configureCircuitBreaker(
+          maxSlotMovementBps,
+          maxWindowMovementBps,
+          new BN(windowSlots)
+        )
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("validates the circuit breaker configuration", async () => {
+      await expectError(
+        configureCircuitBreaker(user, 100, 0, 0),
+        "Unauthorized"
+      );
+      await expectError(
+        configureCircuitBreaker(admin, 100, 300, 0),
+        "InvalidPoolParameter"
+      );
+    });
+
+    it("trips on large price movements", async () => {
+      await configureCircuitBreaker(admin, 100, 300, 150);
+
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      const reserve = pool.reserveOne.toNumber();
+
+      await swap(fixture, user, Math.floor(reserve / 1_000));
+      await expectError(
+        swap(fixture, user, Math.floor(reserve / 10)),
+        "PriceImpactTooHigh"
+      );
+      await expectError(
+        program.methods
+          .quoteSwapExactIn(new BN(Math.floor(reserve / 10)), 0)
+          .accounts(quoteAccounts(fixture))
+          .simulate(),
+        "PriceImpactTooHigh"
+      );
+
+      await configureCircuitBreaker(admin, 0, 0, 0);
+      await swap(flip(fixture), user, Math.floor(reserve / 1_000));
+    });
+  });
+
+  describe("trade limits", () => {
+    const configureTradeLimits = (
+      signer: Keypair,
+      maxTradeSizeBps: number,
+      maxPriceImpactBps: number
+    ) =>
+      program.methods
+        .configureTradeLimits(maxTradeSizeBps, maxPriceImpactBps)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    it("validates the trade limits", async () => {
+      await expectError(configureTradeLimits(user, 500, 0), "Unauthorized");
+      await expectError(
+        configureTradeLimits(admin, 10_001, 0),
+        "InvalidPoolParameter"
+      );
+    });
+
+    it("caps the trade size and the price impact", async () => {
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      const reserve = pool.reserveOne.toNumber();
+
+      await configureTradeLimits(admin, 500, 0);
+      await expectError(
+        swap(fixture, user, Math.floor(reserve / 10)),
+        "TradeSizeTooLarge"
+      );
+
+      await configureTradeLimits(admin, 0, 50);
+      await expectError(
+        swap(fixture, user, Math.floor(reserve / 50)),
+        "PriceImpactTooHigh"
+      );
+      await swap(fixture, user, Math.floor(reserve / 10_000));
+
+      await configureTradeLimits(admin, 0, 0);
+    });
+  });
+
+  describe("skim and sync", () => {
+    const reconcileAccounts = () => ({
+      dexConfigurationAccount: dexConfiguration,
+      pool: fixture.pool,
+      mintTokenOne: fixture.mintOne,
+      mintTokenTwo: fixture.mintTwo,
+      poolTokenAccountOne: fixture.poolTokenAccountOne,
+      poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+    });
+
+    const donate = (amount: number) =>
+      transfer(
+        connection,
+        admin,
+        ata(fixture.mintOne, admin.publicKey),
+        fixture.poolTokenAccountOne,
+        admin,
+        amount
+      );
+
+    it("skims tokens sent to the vaults directly", async () => {
+      await donate(UNIT);
+
+      const recipientTokenAccountOne = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(recipientTokenAccountOne);
+
+      await program.methods
+        .skim()
+        .accounts({
+          ...reconcileAccounts(),
+          recipientTokenAccountOne,
+          recipientTokenAccountTwo: ata(fixture.mintTwo, user.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect((await balance(recipientTokenAccountOne)) - before).to.equal(UNIT);
+    });
+
+    it("syncs the reserves to the vault balances", async () => {
+      const before = await program.account.liquidityPool.fetch(fixture.pool);
+
+      await donate(UNIT);
+      await program.methods.sync().accounts(reconcileAccounts()).rpc();
+
+      const after = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(after.reserveOne.sub(before.reserveOne).toNumber()).to.equal(UNIT);
+      expect(after.reserveTwo.eq(before.reserveTwo)).to.be.true;
+    });
+  });
+
+  describe("closing accounts", () => {
+    it("keeps pools with liquidity open", async () => {
+      await expectError(
+        program.methods
+          .closePool()
+          .accounts({
+            pool: fixture.pool,
+            mintTokenOne: fixture.mintOne,
+            mintTokenTwo: fixture.mintTwo,
+            poolTokenAccountOne: fixture.poolTokenAccountOne,
+            poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+            payerTokenAccountOne: ata(fixture.mintOne, admin.publicKey),
+            payerTokenAccountTwo: ata(fixture.mintTwo, admin.publicKey),
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "PoolNotEmpty"
+      );
+    });
+
+    it("closes an emptied pool and sweeps what is left", async () => {
+      const emptied = await createPool();
+      const liquidityProvider = findLiquidityProvider(
+        emptied.pool,
+        admin.publicKey
+      );
+
+      await addLiquidity(emptied, admin, 100 * UNIT, 100 * UNIT);
+      const { shares } = await program.account.liquidityProvider.fetch(
+        liquidityProvider
+      );
+      await removeLiquidity(emptied, admin, shares.toNumber());
+
+      // A stray transfer must not keep the pool open
+      await transfer(
+        connection,
+        admin,
+        ata(emptied.mintOne, admin.publicKey),
+        emptied.poolTokenAccountOne,
+        admin,
+        UNIT
+      );
+
+      const payerTokenAccountOne = ata(emptied.mintOne, admin.publicKey);
+      const before = await balance(payerTokenAccountOne);
+
+      await program.methods
+        .closeLiquidityProvider()
+        .accounts({
+          pool: emptied.pool,
+          liquidityProviderAccount: liquidityProvider,
+          payer: admin.publicKey,
+          user: admin.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .closePool()
+        .accounts({
+          pool: emptied.pool,
+          mintTokenOne: emptied.mintOne,
+          mintTokenTwo: emptied.mintTwo,
+          poolTokenAccountOne: emptied.poolTokenAccountOne,
+          poolTokenAccountTwo: emptied.poolTokenAccountTwo,
+          payerTokenAccountOne,
+          payerTokenAccountTwo: ata(emptied.mintTwo, admin.publicKey),
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      expect((await balance(payerTokenAccountOne)) - before).to.equal(UNIT);
+      expect(await connection.getAccountInfo(emptied.pool)).to.be.null;
+      expect(await connection.getAccountInfo(liquidityProvider)).to.be.null;
+    });
+  });
+
+  describe("migrations", () => {
+    it("leaves accounts on the current layout alone", async () => {
+      await expectError(
+        program.methods
+          .migrateConfig()
+          .accounts({
+            dexConfigurationAccount: dexConfiguration,
+            newAdmin: null,
+            programData: null,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc(),
+        "AccountAlreadyMigrated"
+      );
+
+      await expectError(
+        program.methods
+          .migratePool()
+          .accounts({
+            dexConfigurationAccount: dexConfiguration,
+            pool: fixture.pool,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc(),
+        "AccountAlreadyMigrated"
+      );
+
+      await expectError(
+        program.methods
+          .migrateLiquidityProvider()
+          .accounts({
+            pool: fixture.pool,
+            liquidityProviderAccount: findLiquidityProvider(
+              fixture.pool,
+              admin.publicKey
+            ),
+            owner: admin.publicKey,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc(),
+        "AccountAlreadyMigrated"
+      );
+    });
+
+    it("records the version of new accounts", async () => {
+      const config = await program.account.dexConfiguration.fetch(
+        dexConfiguration
+      );
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      const liquidityProvider = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(fixture.pool, admin.publicKey)
+      );
+
+      expect(config.version).to.equal(3);
+      expect(pool.version).to.equal(5);
+      expect(liquidityProvider.version).to.equal(5);
+    });
+  });
+
+  describe("share transfers", () => {
+    const transferShares = (destinationOwner: PublicKey, shares: number) =>
+      program.methods
+        .transferShares(new BN(shares))
+        .accounts({
+          pool: fixture.pool,
+          liquidityProviderAccount: findLiquidityProvider(
+            fixture.pool,
+            admin.publicKey
+          ),
+          destinationLiquidityProviderAccount: findLiquidityProvider(
+            fixture.pool,
+            destinationOwner
+          ),
+          destinationOwner,
+          user: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    it("moves shares to another owner", async () => {
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+
+      await transferShares(user.publicKey, 10);
+
+      const destination = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(fixture.pool, user.publicKey)
+      );
+      expect(destination.shares.toNumber()).to.equal(10);
+
+      const after = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(after.totalSupply.eq(pool.totalSupply)).to.be.true;
+    });
+
+    it("rejects transfers to the sender", async () => {
+      await expectError(
+        transferShares(admin.publicKey, 10),
+        "InvalidShareRecipient"
+      );
+    });
+  });
+
+  describe("delegated positions", () => {
+    const delegate = Keypair.generate();
+    const liquidityProvider = () =>
+      findLiquidityProvider(fixture.pool, admin.publicKey);
+
+    const manageAccounts = () => ({
+      pool: fixture.pool,
+      liquidityProviderAccount: liquidityProvider(),
+      user: admin.publicKey,
+    });
+
+    before(async () => {
+      await airdrop(delegate.publicKey);
+      await fundWallet(fixture, delegate.publicKey);
+
+      await program.methods
+        .approveDelegate(delegate.publicKey, new BN(5))
+        .accounts(manageAccounts())
+        .rpc();
+    });
+
+    it("funds deposits from the delegate's own accounts", async () => {
+      const ownerTokenAccount = ata(fixture.mintOne, admin.publicKey);
+      const delegateTokenAccount = ata(fixture.mintOne, delegate.publicKey);
+      const ownerBalance = await balance(ownerTokenAccount);
+      const delegateBalance = await balance(delegateTokenAccount);
+      const { shares } = await program.account.liquidityProvider.fetch(
+        liquidityProvider()
+      );
+
+      await addLiquidity(
+        fixture,
+        delegate,
+        10 * UNIT,
+        10 * UNIT,
+        admin.publicKey
+      );
+
+      const after = await program.account.liquidityProvider.fetch(
+        liquidityProvider()
+      );
+      const added = after.shares.sub(shares).toNumber();
+
+      // Adding to the position leaves the allowance as the owner approved it
+      expect(added).to.be.greaterThan(0);
+      expect(after.delegatedShares.toNumber()).to.equal(5);
+      expect(await balance(ownerTokenAccount)).to.equal(ownerBalance);
+      expect(await balance(delegateTokenAccount)).to.equal(
+        delegateBalance - 10 * UNIT
+      );
+    });
+
+    it("pays withdrawals to the owner up to the allowance", async () => {
+      const { delegatedShares } = await program.account.liquidityProvider.fetch(
+        liquidityProvider()
+      );
+
+      await expectError(
+        removeLiquidity(
+          fixture,
+          delegate,
+          delegatedShares.toNumber() + 1,
+          admin.publicKey
+        ),
+        "DelegatedSharesExceeded"
+      );
+
+      const ownerTokenAccount = ata(fixture.mintOne, admin.publicKey);
+      const ownerBalance = await balance(ownerTokenAccount);
+
+      await removeLiquidity(fixture, delegate, 5, admin.publicKey);
+
+      expect(await balance(ownerTokenAccount)).to.be.greaterThan(ownerBalance);
+    });
+
+    it("locks the delegate out once revoked", async () => {
+      await program.methods.revokeDelegate().accounts(manageAccounts()).rpc();
+
+      await expectError(
+        removeLiquidity(fixture, delegate, 1, admin.publicKey),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("locked positions", () => {
+    const lockAccounts = () => ({
+      pool: fixture.pool,
+      liquidityProviderAccount: findLiquidityProvider(
+        fixture.pool,
+        user.publicKey
+      ),
+      user: user.publicKey,
+    });
+
+    const lockShares = (shares: number, unlockTimestamp: number) =>
+      program.methods
+        .lockShares(new BN(shares), new BN(unlockTimestamp))
+        .accounts(lockAccounts())
+        .signers([user])
+        .rpc();
+
+    const extendLock = (unlockTimestamp: number) =>
+      program.methods
+        .extendLock(new BN(unlockTimestamp))
+        .accounts(lockAccounts())
+        .signers([user])
+        .rpc();
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    it("keeps locked shares in the pool", async () => {
+      await expectError(lockShares(5, now() - 60), "InvalidLock");
+
+      const unlockTimestamp = now() + 3_600;
+      await lockShares(5, unlockTimestamp);
+
+      await expectError(removeLiquidity(fixture, user, 6), "SharesLocked");
+      await removeLiquidity(fixture, user, 5);
+
+      const liquidityProvider = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(fixture.pool, user.publicKey)
+      );
+      expect(liquidityProvider.lockedShares.toNumber()).to.equal(5);
+      expect(liquidityProvider.lockUntil.toNumber()).to.equal(unlockTimestamp);
+    });
+
+    it("only ever extends a lock", async () => {
+      const liquidityProvider = await program.account.liquidityProvider.fetch(
+        findLiquidityProvider(fixture.pool, user.publicKey)
+      );
+      const lockUntil = liquidityProvider.lockUntil.toNumber();
+
+      await expectError(extendLock(lockUntil - 60), "InvalidLock");
+      await extendLock(lockUntil + 60);
+    });
+  });
+
+  describe("fee claims", () => {
+    const claimFees = () =>
+      program.methods
+        .claimFees(false)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          liquidityProviderAccount: findLiquidityProvider(
+            fixture.pool,
+            admin.publicKey
+          ),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          userTokenAccountOne: ata(fixture.mintOne, admin.publicKey),
+          userTokenAccountTwo: ata(fixture.mintTwo, admin.publicKey),
+          nativeSolAccount: null,
+          nativeMint: null,
+          user: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    it("pays out the fees earned by a position", async () => {
+      await swap(fixture, user, 10 * UNIT);
+
+      const tokenAccount = ata(fixture.mintOne, admin.publicKey);
+      const before = await balance(tokenAccount);
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+
+      await claimFees();
+
+      const after = await program.account.liquidityPool.fetch(fixture.pool);
+      const claimed = (await balance(tokenAccount)) - before;
+
+      expect(claimed).to.be.greaterThan(0);
+      expect(pool.feeReserveOne.sub(after.feeReserveOne).toNumber()).to.equal(
+        claimed
+      );
+    });
+
+    it("has nothing to pay out right after a claim", async () => {
+      await expectError(claimFees(), "NoFeesToClaim");
+    });
+  });
+
+  describe("liquidity mining", () => {
+    let rewardMint: PublicKey;
+    let rewardVault: PublicKey;
+
+    const setEmissionRate = (
+      signer: Keypair,
+      emissionRate: number,
+      endTime: number
+    ) =>
+      program.methods
+        .setEmissionRate(0, new BN(emissionRate), new BN(endTime))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          rewardMint,
+          rewardVaultTokenAccount: rewardVault,
+          compoundingVault: pda(
+            Buffer.from("compounding_vault"),
+            fixture.pool.toBuffer()
+          ),
+          admin: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+
+    const claimRewards = (signer: Keypair, rewardIndex = 0) =>
+      program.methods
+        .claimRewards(rewardIndex)
+        .accounts({
+          pool: fixture.pool,
+          liquidityProviderAccount: findLiquidityProvider(
+            fixture.pool,
+            signer.publicKey
+          ),
+          rewardMint,
+          rewardVaultTokenAccount: rewardVault,
+          userRewardTokenAccount: ata(rewardMint, signer.publicKey),
+          user: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      rewardMint = await createTestMint();
+      rewardVault = pda(
+        Buffer.from("reward_vault"),
+        fixture.pool.toBuffer(),
+        rewardMint.toBuffer()
+      );
+    });
+
+    it("lets the admin start an emission", async () => {
+      const endTime = Math.floor(Date.now() / 1000) + 3_600;
+
+      await expectError(setEmissionRate(user, UNIT, endTime), "Unauthorized");
+      await setEmissionRate(admin, UNIT, endTime);
+
+      const funderTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        admin,
+        rewardMint,
+        admin.publicKey
+      );
+      await mintTo(
+        connection,
+        admin,
+        rewardMint,
+        funderTokenAccount.address,
+        admin,
+        1_000 * UNIT
+      );
+
+      await program.methods
+        .fundRewards(0, new BN(1_000 * UNIT))
+        .accounts({
+          pool: fixture.pool,
+          rewardMint,
+          rewardVaultTokenAccount: rewardVault,
+          funderTokenAccount: funderTokenAccount.address,
+          funder: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(pool.rewards[0].mint.equals(rewardMint)).to.be.true;
+      expect(pool.rewards[0].undistributed.toNumber()).to.equal(1_000 * UNIT);
+    });
+
+    it("pays rewards to liquidity providers", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 2_000));
+
+      await claimRewards(admin);
+
+      const claimed = await balance(ata(rewardMint, admin.publicKey));
+      expect(claimed).to.be.greaterThan(0);
+    });
+
+    it("rejects claims on unused reward slots", async () => {
+      await expectError(claimRewards(admin, 1), "InvalidRewardVault");
+    });
+
+    it("keeps the reward vault open while the pool has liquidity", async () => {
+      await expectError(
+        program.methods
+          .closeRewardVault(0)
+          .accounts({
+            pool: fixture.pool,
+            rewardMint,
+            rewardVaultTokenAccount: rewardVault,
+            payerRewardTokenAccount: ata(rewardMint, admin.publicKey),
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "PoolNotEmpty"
+      );
+    });
+  });
+
+  describe("gauges", () => {
+    const EPOCH_DURATION = 6;
+    const EMISSIONS_PER_EPOCH = 1_000 * UNIT;
+
+    let governanceMint: PublicKey;
+    let emissionMint: PublicKey;
+
+    const gaugeController = pda(Buffer.from("GaugeController"));
+    const lockerVault = pda(Buffer.from("locker_vault"));
+    const emissionVault = pda(Buffer.from("emission_vault"));
+    const voteEscrow = pda(
+      Buffer.from("vote_escrow"),
+      user.publicKey.toBuffer()
+    );
+
+    const gauge = () => pda(Buffer.from("gauge"), fixture.pool.toBuffer());
+
+    const rewardVault = () =>
+      pda(
+        Buffer.from("reward_vault"),
+        fixture.pool.toBuffer(),
+        emissionMint.toBuffer()
+      );
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    const initializeGaugeController = (epochDuration: number) =>
+      program.methods
+        .initializeGaugeController(
+          new BN(epochDuration),
+          new BN(EMISSIONS_PER_EPOCH)
+        )
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          gaugeController,
+          governanceMint,
+          emissionMint,
+          lockerVault,
+          emissionVault,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const createGauge = (signer: Keypair) =>
+      program.methods
+        .createGauge()
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          gaugeController,
+          pool: fixture.pool,
+          gauge: gauge(),
+          admin: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    const lockGovernanceTokens = (amount: number, unlockTime: number) =>
+      program.methods
+        .lockGovernanceTokens(new BN(amount), new BN(unlockTime))
+        .accounts({
+          gaugeController,
+          voteEscrow,
+          lockerVault,
+          userTokenAccount: ata(governanceMint, user.publicKey),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    const voteGauge = (voteBps: number) =>
+      program.methods
+        .voteGauge(voteBps)
+        .accounts({
+          gaugeController,
+          gauge: gauge(),
+          voteEscrow,
+          gaugeVote: pda(
+            Buffer.from("gauge_vote"),
+            gauge().toBuffer(),
+            user.publicKey.toBuffer()
+          ),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    const distributeEmissions = () =>
+      program.methods
+        .distributeEmissions(1)
+        .accounts({
+          gaugeController,
+          gauge: gauge(),
+          pool: fixture.pool,
+          emissionVault,
+          rewardVaultTokenAccount: rewardVault(),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    before(async () => {
+      governanceMint = await createTestMint();
+      emissionMint = await createTestMint();
+
+      const userTokenAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        admin,
+        governanceMint,
+        user.publicKey
+      );
+      await mintTo(
+        connection,
+        admin,
+        governanceMint,
+        userTokenAccount.address,
+        admin,
+        1_000 * UNIT
+      );
+    });
+
+    it("sets up the gauge controller", async () => {
+      await expectError(initializeGaugeController(0), "InvalidEpochDuration");
+      await initializeGaugeController(EPOCH_DURATION);
+
+      await mintTo(
+        connection,
+        admin,
+        emissionMint,
+        emissionVault,
+        admin,
+        10 * EMISSIONS_PER_EPOCH
+      );
+
+      // Gauge emissions are streamed through the pool's second reward slot
+      await program.methods
+        .setEmissionRate(1, new BN(0), new BN(now()))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          rewardMint: emissionMint,
+          rewardVaultTokenAccount: rewardVault(),
+          compoundingVault: pda(
+            Buffer.from("compounding_vault"),
+            fixture.pool.toBuffer()
+          ),
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    it("lets only the admin create gauges", async () => {
+      await expectError(createGauge(user), "Unauthorized");
+      await createGauge(admin);
+
+      const account = await program.account.gauge.fetch(gauge());
+      expect(account.pool.equals(fixture.pool)).to.be.true;
+    });
+
+    it("locks governance tokens for voting power", async () => {
+      await expectError(
+        lockGovernanceTokens(100 * UNIT, now() - 60),
+        "InvalidLock"
+      );
+
+      const unlockTime = now() + 365 * 24 * 60 * 60;
+      await lockGovernanceTokens(100 * UNIT, unlockTime);
+
+      // Locks are never shortened
+      await expectError(
+        lockGovernanceTokens(0, unlockTime - 60),
+        "InvalidLock"
+      );
+
+      const account = await program.account.voteEscrow.fetch(voteEscrow);
+      expect(account.amount.toNumber()).to.equal(100 * UNIT);
+      expect(account.lockEnd.toNumber()).to.equal(unlockTime);
+      expect(await balance(lockerVault)).to.equal(100 * UNIT);
+    });
+
+    it("keeps governance tokens locked until the lock ends", async () => {
+      await expectError(
+        program.methods
+          .withdrawGovernanceTokens()
+          .accounts({
+            gaugeController,
+            voteEscrow,
+            lockerVault,
+            userTokenAccount: ata(governanceMint, user.publicKey),
+            user: user.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+        "GovernanceTokensLocked"
+      );
+    });
+
+    it("caps votes at the full voting power", async () => {
+      await expectError(voteGauge(10_001), "VotingPowerExceeded");
+
+      // Voting again replaces the earlier vote
+      await voteGauge(5_000);
+      await voteGauge(10_000);
+
+      const account = await program.account.voteEscrow.fetch(voteEscrow);
+      expect(account.usedVoteBps).to.equal(10_000);
+    });
+
+    it("streams the gauge's share of emissions to the pool", async () => {
+      await new Promise((resolve) =>
+        setTimeout(resolve, (EPOCH_DURATION + 1) * 1_000)
+      );
+
+      await distributeEmissions();
+
+      // The only gauge with votes receives everything emitted in the epoch
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      expect(pool.rewards[1].undistributed.toNumber()).to.equal(
+        EMISSIONS_PER_EPOCH
+      );
+      expect(await balance(rewardVault())).to.equal(EMISSIONS_PER_EPOCH);
+
+      await expectError(distributeEmissions(), "EmissionsAlreadyDistributed");
+    });
+  });
+
+  describe("compounding vaults", () => {
+    let vaultFixture: PoolFixture;
+
+    const vault = () =>
+      pda(Buffer.from("compounding_vault"), vaultFixture.pool.toBuffer());
+
+    const shareMint = () =>
+      pda(Buffer.from("vault_share_mint"), vault().toBuffer());
+
+    const vaultLiquidityProvider = () =>
+      findLiquidityProvider(vaultFixture.pool, vault());
+
+    const createCompoundingVault = (pool: PublicKey) => {
+      const vaultAddress = pda(
+        Buffer.from("compounding_vault"),
+        pool.toBuffer()
+      );
+
+      return program.methods
+        .createCompoundingVault()
+        .accounts({
+          pool,
+          vault: vaultAddress,
+          shareMint: pda(
+            Buffer.from("vault_share_mint"),
+            vaultAddress.toBuffer()
+          ),
+          vaultLiquidityProviderAccount: findLiquidityProvider(
+            pool,
+            vaultAddress
+          ),
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    };
+
+    const harvest = (minSharesOut = 0) =>
+      program.methods
+        .harvest(null, new BN(minSharesOut))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: vaultFixture.pool,
+          vault: vault(),
+          vaultLiquidityProviderAccount: vaultLiquidityProvider(),
+          mintTokenOne: vaultFixture.mintOne,
+          mintTokenTwo: vaultFixture.mintTwo,
+          poolTokenAccountOne: vaultFixture.poolTokenAccountOne,
+          poolTokenAccountTwo: vaultFixture.poolTokenAccountTwo,
+          rewardMint: null,
+          rewardVaultTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const userShares = async () =>
+      (
+        await program.account.liquidityProvider.fetch(
+          findLiquidityProvider(vaultFixture.pool, user.publicKey)
+        )
+      ).shares.toNumber();
+
+    before(async () => {
+      vaultFixture = await createPool();
+
+      await addLiquidity(vaultFixture, admin, 1_000 * UNIT, 1_000 * UNIT);
+      await addLiquidity(vaultFixture, user, 100 * UNIT, 100 * UNIT);
+    });
+
+    it("refuses pools paying rewards it cannot compound", async () => {
+      await expectError(
+        createCompoundingVault(fixture.pool),
+        "RewardNotCompoundable"
+      );
+    });
+
+    it("mints vault shares for deposited liquidity", async () => {
+      await createCompoundingVault(vaultFixture.pool);
+
+      const shares = Math.floor((await userShares()) / 2);
+
+      await program.methods
+        .depositToVault(new BN(shares))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: vaultFixture.pool,
+          vault: vault(),
+          vaultLiquidityProviderAccount: vaultLiquidityProvider(),
+          liquidityProviderAccount: findLiquidityProvider(
+            vaultFixture.pool,
+            user.publicKey
+          ),
+          shareMint: shareMint(),
+          userShareTokenAccount: ata(shareMint(), user.publicKey),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      // The first deposit pays for the dead shares
+      expect(await balance(ata(shareMint(), user.publicKey))).to.equal(
+        shares - 1_000
+      );
+    });
+
+    it("reinvests the fees earned by the vault", async () => {
+      await expectError(harvest(), "NothingToHarvest");
+
+      await swap(vaultFixture, admin, 50 * UNIT);
+      await swap(flip(vaultFixture), admin, 50 * UNIT);
+
+      const before = await program.account.liquidityProvider.fetch(
+        vaultLiquidityProvider()
+      );
+
+      await expectError(
+        harvest(Number.MAX_SAFE_INTEGER),
+        "HarvestBelowMinimum"
+      );
+      await harvest();
+
+      const after = await program.account.liquidityProvider.fetch(
+        vaultLiquidityProvider()
+      );
+      expect(after.shares.toNumber()).to.be.greaterThan(
+        before.shares.toNumber()
+      );
+    });
+
+    it("returns more liquidity than was deposited", async () => {
+      const userShareTokenAccount = ata(shareMint(), user.publicKey);
+      const vaultShares = await balance(userShareTokenAccount);
+      const sharesBefore = await userShares();
+
+      await program.methods
+        .withdrawFromVault(new BN(vaultShares))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: vaultFixture.pool,
+          vault: vault(),
+          vaultLiquidityProviderAccount: vaultLiquidityProvider(),
+          liquidityProviderAccount: findLiquidityProvider(
+            vaultFixture.pool,
+            user.publicKey
+          ),
+          shareMint: shareMint(),
+          userShareTokenAccount,
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      expect(await balance(userShareTokenAccount)).to.equal(0);
+      expect((await userShares()) - sharesBefore).to.be.greaterThan(
+        vaultShares
+      );
+    });
+  });
+
+  describe("referrals", () => {
+    const referrer = Keypair.generate();
+
+    const referrerStats = () =>
+      pda(
+        Buffer.from("referrer_stats"),
+        referrer.publicKey.toBuffer(),
+        fixture.mintOne.toBuffer()
+      );
+
+    const configureReferralFee = (signer: Keypair, maxReferralFeeBps: number) =>
+      program.methods
+        .configureReferralFee(maxReferralFeeBps)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const referredSwap = (
+      amount: number,
+      referralFeeBps: number,
+      referred = true
+    ) =>
+      program.methods
+        .swap(new BN(amount), false, null, referralFeeBps)
+        .accounts({
+          ...swapAccounts(fixture, user),
+          referrerTokenAccount: referred
+            ? ata(fixture.mintOne, referrer.publicKey)
+            : null,
+          referrerStats: referred ? referrerStats() : null,
+        })
+        .signers([user])
+        .rpc();
+
+    before(async () => {
+      await airdrop(referrer.publicKey);
+
+      await getOrCreateAssociatedTokenAccount(
+        connection,
+        admin,
+        fixture.mintOne,
+        referrer.publicKey
+      );
+    });
+
+    it("lets the admin cap referral fees", async () => {
+      await expectError(configureReferralFee(user, 50), "Unauthorized");
+      await expectError(configureReferralFee(admin, 10_001), "InvalidFee");
+      await configureReferralFee(admin, 50);
+
+      const configuration = await program.account.dexConfiguration.fetch(
+        dexConfiguration
+      );
+      expect(configuration.maxReferralFeeBps).to.equal(50);
+    });
+
+    it("registers a referrer per input mint", async () => {
+      await program.methods
+        .registerReferrer()
+        .accounts({
+          referrerStats: referrerStats(),
+          mint: fixture.mintOne,
+          referrer: referrer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([referrer])
+        .rpc();
+
+      const stats = await program.account.referrerStats.fetch(referrerStats());
+      expect(stats.referrer.equals(referrer.publicKey)).to.be.true;
+      expect(stats.swapCount.toNumber()).to.equal(0);
+    });
+
+    it("pays the referrer a cut of the input", async () => {
+      await expectError(referredSwap(10 * UNIT, 51), "ReferralFeeTooHigh");
+      await expectError(
+        referredSwap(10 * UNIT, 50, false),
+        "InvalidReferrer"
+      );
+
+      await referredSwap(10 * UNIT, 50);
+
+      expect(await balance(ata(fixture.mintOne, referrer.publicKey))).to.equal(
+        (10 * UNIT * 50) / 10_000
+      );
+
+      const stats = await program.account.referrerStats.fetch(referrerStats());
+      expect(stats.swapCount.toNumber()).to.equal(1);
+      expect(stats.volume.toNumber()).to.equal(10 * UNIT);
+      expect(stats.feesEarned.toNumber()).to.equal((10 * UNIT * 50) / 10_000);
+    });
+  });
+
+  describe("staking", () => {
+    let governanceMint: PublicKey;
+
+    const gaugeController = pda(Buffer.from("GaugeController"));
+    const stakeAccount = pda(Buffer.from("stake"), user.publicKey.toBuffer());
+    const stakeVault = pda(Buffer.from("stake_vault"));
+    const stakeVaultAuthority = pda(Buffer.from("stake_vault_authority"));
+
+    const configureFeeDiscounts = (
+      signer: Keypair,
+      tiers: { minStake: BN; discountBps: number }[]
+    ) =>
+      program.methods
+        .configureFeeDiscounts(tiers)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          admin: signer.publicKey,
+        })
+        .signers([signer])
+        .rpc();
+
+    const stake = (amount: number) =>
+      program.methods
+        .stake(new BN(amount))
+        .accounts({
+          gaugeController,
+          stakeAccount,
+          stakeVault,
+          stakeVaultAuthority,
+          governanceMint,
+          userTokenAccount: ata(governanceMint, user.publicKey),
+          user: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    const unstake = (amount: number) =>
+      program.methods
+        .unstake(new BN(amount))
+        .accounts({
+          stakeAccount,
+          stakeVault,
+          stakeVaultAuthority,
+          userTokenAccount: ata(governanceMint, user.publicKey),
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    const quote = (withStake: boolean) =>
+      returnData(
+        program.methods
+          .quoteSwapExactIn(new BN(10 * UNIT), 0)
+          .accounts({
+            ...quoteAccounts(fixture),
+            stakeAccount: withStake ? stakeAccount : null,
+          }),
+        "SwapAmounts"
+      );
+
+    before(async () => {
+      // Stakes are held in the gauge controller's governance token
+      const controller = await program.account.gaugeController.fetch(
+        gaugeController
+      );
+      governanceMint = controller.governanceMint;
+    });
+
+    it("lets the admin configure discount tiers", async () => {
+      const tier = (minStake: number, discountBps: number) => ({
+        minStake: new BN(minStake),
+        discountBps,
+      });
+
+      await expectError(
+        configureFeeDiscounts(user, [tier(10 * UNIT, 1_000)]),
+        "Unauthorized"
+      );
+      await expectError(
+        configureFeeDiscounts(admin, [tier(10 * UNIT, 10_001)]),
+        "InvalidFee"
+      );
+      await expectError(
+        configureFeeDiscounts(
+          admin,
+          [1, 2, 3, 4, 5].map((i) => tier(i * UNIT, 100))
+        ),
+        "InvalidFee"
+      );
+
+      await configureFeeDiscounts(admin, [
+        tier(10 * UNIT, 1_000),
+        tier(100 * UNIT, 2_500),
+      ]);
+
+      const configuration = await program.account.dexConfiguration.fetch(
+        dexConfiguration
+      );
+      expect(configuration.feeDiscountTiers[1].discountBps).to.equal(2_500);
+      expect(configuration.feeDiscountTiers[2].minStake.toNumber()).to.equal(0);
+    });
+
+    it("only discounts stake that has warmed up", async () => {
+      await expectError(stake(0), "InvalidAmount");
+      await stake(100 * UNIT);
+
+      const account = await program.account.stakeAccount.fetch(stakeAccount);
+      expect(account.amount.toNumber()).to.equal(100 * UNIT);
+      expect(account.warmingAmount.toNumber()).to.equal(100 * UNIT);
+      expect(await balance(stakeVault)).to.equal(100 * UNIT);
+
+      const [staked, unstaked] = [await quote(true), await quote(false)];
+      expect(staked.feeAmount.toNumber()).to.equal(
+        unstaked.feeAmount.toNumber()
+      );
+    });
+
+    it("returns staked tokens", async () => {
+      const userTokenAccount = ata(governanceMint, user.publicKey);
+      const before = await balance(userTokenAccount);
+
+      await expectError(unstake(100 * UNIT + 1), "InvalidAmount");
+      await unstake(100 * UNIT);
+
+      expect((await balance(userTokenAccount)) - before).to.equal(100 * UNIT);
+
+      const account = await program.account.stakeAccount.fetch(stakeAccount);
+      expect(account.amount.toNumber()).to.equal(0);
+    });
+  });
+
+  describe("limit orders", () => {
+    const now = () => Math.floor(Date.now() / 1000);
+
+    const order = (orderId: number) =>
+      pda(
+        Buffer.from("order"),
+        fixture.pool.toBuffer(),
+        user.publicKey.toBuffer(),
+        new BN(orderId).toArrayLike(Buffer, "le", 8)
+      );
+
+    const escrow = (orderId: number) =>
+      pda(Buffer.from("order_escrow"), order(orderId).toBuffer());
+
+    const placeOrder = (
+      orderId: number,
+      amountIn: number,
+      limitPrice: number,
+      expiry = now() + 3_600
+    ) =>
+      program.methods
+        .placeOrder(
+          new BN(orderId),
+          new BN(amountIn),
+          limitPrice,
+          new BN(expiry)
+        )
+        .accounts({
+          pool: fixture.pool,
+          order: order(orderId),
+          escrow: escrow(orderId),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          ownerTokenAccount: ata(fixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    // Keepers, here the admin, fill orders for a cut of the output
+    const fillOrder = (orderId: number, amountIn: number) =>
+      program.methods
+        .fillOrder(new BN(amountIn))
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          order: order(orderId),
+          escrow: escrow(orderId),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          ownerTokenAccount: ata(fixture.mintTwo, user.publicKey),
+          ownerInputTokenAccount: ata(fixture.mintOne, user.publicKey),
+          keeperTokenAccount: ata(fixture.mintTwo, admin.publicKey),
+          owner: user.publicKey,
+          keeper: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const cancelOrder = (orderId: number, authority: Keypair) =>
+      program.methods
+        .cancelOrder()
+        .accounts({
+          order: order(orderId),
+          escrow: escrow(orderId),
+          ownerTokenAccount: ata(fixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          authority: authority.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([authority])
+        .rpc();
+
+    it("escrows the input of an order", async () => {
+      await expectError(
+        placeOrder(1, 10 * UNIT, 0.5, now() - 60),
+        "OrderExpired"
+      );
+      await expectError(placeOrder(1, 10 * UNIT, 0), "InvalidPoolParameter");
+
+      await placeOrder(1, 10 * UNIT, 0.5);
+      await placeOrder(2, 10 * UNIT, 2);
+
+      expect(await balance(escrow(1))).to.equal(10 * UNIT);
+
+      const account = await program.account.order.fetch(order(1));
+      expect(account.remainingAmountIn.toNumber()).to.equal(10 * UNIT);
+    });
+
+    it("fills orders only at their limit price", async () => {
+      await expectError(fillOrder(2, UNIT), "LimitPriceNotReached");
+      await expectError(fillOrder(1, 10 * UNIT + 1), "InvalidAmount");
+
+      const ownerTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await fillOrder(1, 4 * UNIT);
+
+      const account = await program.account.order.fetch(order(1));
+      expect(account.remainingAmountIn.toNumber()).to.equal(6 * UNIT);
+      expect((await balance(ownerTokenAccount)) - before).to.equal(
+        account.filledAmountOut.toNumber()
+      );
+      expect(account.filledAmountOut.toNumber()).to.be.at.least(2 * UNIT);
+    });
+
+    it("closes an order once it is fully filled", async () => {
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await donate(fixture.mintOne, escrow(1), UNIT);
+      await fillOrder(1, 6 * UNIT);
+
+      expect(await program.account.order.fetchNullable(order(1))).to.be.null;
+      expect((await balance(ownerTokenAccount)) - before).to.equal(UNIT);
+    });
+
+    it("returns the escrow of a cancelled order", async () => {
+      await expectError(cancelOrder(2, admin), "Unauthorized");
+
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await cancelOrder(2, user);
+
+      expect((await balance(ownerTokenAccount)) - before).to.equal(10 * UNIT);
+      expect(await program.account.order.fetchNullable(order(2))).to.be.null;
+    });
+
+    it("returns tokens sent to the escrow on cancel", async () => {
+      await placeOrder(3, 10 * UNIT, 2);
+      await donate(fixture.mintOne, escrow(3), UNIT);
+
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await cancelOrder(3, user);
+
+      expect((await balance(ownerTokenAccount)) - before).to.equal(11 * UNIT);
+      expect(await connection.getAccountInfo(escrow(3))).to.be.null;
+    });
+  });
+
+  describe("long-term orders", () => {
+    const longTermOrder = (orderId: number) =>
+      pda(
+        Buffer.from("long_term_order"),
+        fixture.pool.toBuffer(),
+        user.publicKey.toBuffer(),
+        new BN(orderId).toArrayLike(Buffer, "le", 8)
+      );
+
+    const placeLongTermOrder = (
+      orderId: number,
+      amountIn: number,
+      intervals: number
+    ) =>
+      program.methods
+        .placeLongTermOrder(
+          new BN(orderId),
+          new BN(amountIn),
+          new BN(intervals)
+        )
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          longTermOrder: longTermOrder(orderId),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          ownerTokenAccount: ata(fixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    const settleAccounts = (orderId: number, authority: Keypair) => ({
+      dexConfigurationAccount: dexConfiguration,
+      pool: fixture.pool,
+      longTermOrder: longTermOrder(orderId),
+      mintTokenOne: fixture.mintOne,
+      mintTokenTwo: fixture.mintTwo,
+      poolTokenAccountOne: fixture.poolTokenAccountOne,
+      poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+      ownerTokenAccountOne: ata(fixture.mintOne, user.publicKey),
+      ownerTokenAccountTwo: ata(fixture.mintTwo, user.publicKey),
+      owner: user.publicKey,
+      authority: authority.publicKey,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    });
+
+    it("spreads an order over whole intervals", async () => {
+      await expectError(
+        placeLongTermOrder(1, 24 * UNIT, 0),
+        "InvalidPoolParameter"
+      );
+      await expectError(
+        placeLongTermOrder(1, 25 * UNIT, 25),
+        "InvalidPoolParameter"
+      );
+      await expectError(placeLongTermOrder(1, 23, 24), "InvalidAmount");
+
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      // The remainder that does not divide evenly stays with the owner
+      await placeLongTermOrder(1, 24 * UNIT + 5, 24);
+
+      expect(before - (await balance(ownerTokenAccount))).to.equal(24 * UNIT);
+
+      const order = await program.account.longTermOrder.fetch(longTermOrder(1));
+      expect(order.sellRate.toNumber()).to.equal(UNIT);
+
+      // Order pools are indexed by the pool's own token order
+      const pool = await program.account.liquidityPool.fetch(fixture.pool);
+      const side = pool.tokenOne.equals(fixture.mintOne) ? 0 : 1;
+      expect(pool.twamm.orderPools[side].sellRate.toNumber()).to.equal(UNIT);
+    });
+
+    it("has nothing to withdraw before an interval is sold", async () => {
+      await expectError(
+        program.methods
+          .withdrawLongTermOrder()
+          .accounts(settleAccounts(1, admin))
+          .rpc(),
+        "NothingToWithdraw"
+      );
+    });
+
+    it("refunds the unsold input of a cancelled order", async () => {
+      await expectError(
+        program.methods
+          .cancelLongTermOrder()
+          .accounts(settleAccounts(1, admin))
+          .rpc(),
+        "Unauthorized"
+      );
+
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await program.methods
+        .cancelLongTermOrder()
+        .accounts(settleAccounts(1, user))
+        .signers([user])
+        .rpc();
+
+      // At most an interval can have been sold since the order was placed
+      expect((await balance(ownerTokenAccount)) - before).to.be.at.least(
+        23 * UNIT
+      );
+      expect(
+        await program.account.longTermOrder.fetchNullable(longTermOrder(1))
+      ).to.be.null;
+    });
+  });
+
+  describe("dca positions", () => {
+    const dcaPosition = (positionId: number) =>
+      pda(
+        Buffer.from("dca_position"),
+        fixture.pool.toBuffer(),
+        user.publicKey.toBuffer(),
+        new BN(positionId).toArrayLike(Buffer, "le", 8)
+      );
+
+    const escrow = (positionId: number) =>
+      pda(Buffer.from("dca_escrow"), dcaPosition(positionId).toBuffer());
+
+    const openDcaPosition = (
+      positionId: number,
+      amountPerCycle: number,
+      cycleInterval: number,
+      cycles: number,
+      minAmountOut = 0
+    ) =>
+      program.methods
+        .openDcaPosition(
+          new BN(positionId),
+          new BN(amountPerCycle),
+          new BN(cycleInterval),
+          new BN(cycles),
+          new BN(minAmountOut)
+        )
+        .accounts({
+          pool: fixture.pool,
+          dcaPosition: dcaPosition(positionId),
+          escrow: escrow(positionId),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          ownerTokenAccount: ata(fixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    // Keepers, here the admin, execute cycles for a cut of the output
+    const executeDcaCycle = (positionId: number) =>
+      program.methods
+        .executeDcaCycle()
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          dcaPosition: dcaPosition(positionId),
+          escrow: escrow(positionId),
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          ownerTokenAccount: ata(fixture.mintTwo, user.publicKey),
+          ownerInputTokenAccount: ata(fixture.mintOne, user.publicKey),
+          keeperTokenAccount: ata(fixture.mintTwo, admin.publicKey),
+          owner: user.publicKey,
+          keeper: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    const closeDcaPosition = (positionId: number) =>
+      program.methods
+        .closeDcaPosition()
+        .accounts({
+          dcaPosition: dcaPosition(positionId),
+          escrow: escrow(positionId),
+          ownerTokenAccount: ata(fixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    it("escrows every cycle up front", async () => {
+      await expectError(
+        openDcaPosition(1, 2 * UNIT, 3_600, 0),
+        "InvalidAmount"
+      );
+      await expectError(
+        openDcaPosition(1, 2 * UNIT, 0, 3),
+        "InvalidPoolParameter"
+      );
+
+      await openDcaPosition(1, 2 * UNIT, 3_600, 3);
+
+      expect(await balance(escrow(1))).to.equal(6 * UNIT);
+    });
+
+    it("executes one cycle per interval", async () => {
+      const ownerTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await executeDcaCycle(1);
+
+      expect(await balance(ownerTokenAccount)).to.be.greaterThan(before);
+      expect(await balance(escrow(1))).to.equal(4 * UNIT);
+
+      const position = await program.account.dcaPosition.fetch(dcaPosition(1));
+      expect(position.cyclesRemaining.toNumber()).to.equal(2);
+
+      await expectError(executeDcaCycle(1), "DcaCycleNotDue");
+    });
+
+    it("skips cycles below the minimum output", async () => {
+      await openDcaPosition(2, 2 * UNIT, 3_600, 1, 4 * UNIT);
+
+      await expectError(executeDcaCycle(2), "LimitPriceNotReached");
+    });
+
+    it("closes the position after its last cycle", async () => {
+      await openDcaPosition(3, 2 * UNIT, 3_600, 1);
+      await donate(fixture.mintOne, escrow(3), UNIT);
+
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await executeDcaCycle(3);
+
+      expect(
+        await program.account.dcaPosition.fetchNullable(dcaPosition(3))
+      ).to.be.null;
+      expect((await balance(ownerTokenAccount)) - before).to.equal(UNIT);
+    });
+
+    it("returns the unspent input when closed", async () => {
+      const ownerTokenAccount = ata(fixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await closeDcaPosition(1);
+      await closeDcaPosition(2);
+
+      expect((await balance(ownerTokenAccount)) - before).to.equal(6 * UNIT);
+      expect(
+        await program.account.dcaPosition.fetchNullable(dcaPosition(1))
+      ).to.be.null;
+    });
+  });
+
+  describe("trigger orders", () => {
+    let triggerFixture: PoolFixture;
+
+    const triggerOrder = (orderId: number) =>
+      pda(
+        Buffer.from("trigger_order"),
+        triggerFixture.pool.toBuffer(),
+        user.publicKey.toBuffer(),
+        new BN(orderId).toArrayLike(Buffer, "le", 8)
+      );
+
+    const escrow = (orderId: number) =>
+      pda(
+        Buffer.from("trigger_order_escrow"),
+        triggerOrder(orderId).toBuffer()
+      );
+
+    const placeTriggerOrder = (
+      orderId: number,
+      amountIn: number,
+      triggerPrice: number,
+      isTakeProfit: boolean
+    ) =>
+      program.methods
+        .placeTriggerOrder(
+          new BN(orderId),
+          new BN(amountIn),
+          triggerPrice,
+          isTakeProfit,
+          new BN(0)
+        )
+        .accounts({
+          pool: triggerFixture.pool,
+          triggerOrder: triggerOrder(orderId),
+          escrow: escrow(orderId),
+          mintTokenOne: triggerFixture.mintOne,
+          mintTokenTwo: triggerFixture.mintTwo,
+          ownerTokenAccount: ata(triggerFixture.mintOne, user.publicKey),
+          owner: user.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+    before(async () => {
+      triggerFixture = await createPool();
+
+      await addLiquidity(triggerFixture, admin, 1_000 * UNIT, 1_000 * UNIT);
+    });
+
+    it("escrows the input of a trigger order", async () => {
+      await expectError(placeTriggerOrder(1, 0, 0.5, false), "InvalidAmount");
+      await expectError(
+        placeTriggerOrder(1, 10 * UNIT, 0, false),
+        "InvalidPoolParameter"
+      );
+
+      await placeTriggerOrder(1, 10 * UNIT, 0.5, false);
+
+      expect(await balance(escrow(1))).to.equal(10 * UNIT);
+
+      const order = await program.account.triggerOrder.fetch(triggerOrder(1));
+      expect(order.triggerPrice).to.equal(0.5);
+      expect(order.isTakeProfit).to.be.false;
+    });
+
+    it("waits for a full TWAP window of price history", async () => {
+      // Keepers, here the admin, execute orders for a cut of the output
+      await expectError(
+        program.methods
+          .triggerOrder()
+          .accounts({
+            dexConfigurationAccount: dexConfiguration,
+            pool: triggerFixture.pool,
+            triggerOrder: triggerOrder(1),
+            escrow: escrow(1),
+            mintTokenOne: triggerFixture.mintOne,
+            mintTokenTwo: triggerFixture.mintTwo,
+            poolTokenAccountOne: triggerFixture.poolTokenAccountOne,
+            poolTokenAccountTwo: triggerFixture.poolTokenAccountTwo,
+            ownerTokenAccount: ata(triggerFixture.mintTwo, user.publicKey),
+            ownerInputTokenAccount: ata(
+              triggerFixture.mintOne,
+              user.publicKey
+            ),
+            keeperTokenAccount: ata(triggerFixture.mintTwo, admin.publicKey),
+            owner: user.publicKey,
+            keeper: admin.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc(),
+        "OracleUnavailable"
+      );
+    });
+
+    it("returns the escrow of a cancelled order", async () => {
+      const ownerTokenAccount = ata(triggerFixture.mintOne, user.publicKey);
+      const before = await balance(ownerTokenAccount);
+
+      await donate(triggerFixture.mintOne, escrow(1), UNIT);
+
+      await program.methods
+        .cancelTriggerOrder()
+        .accounts({
+          triggerOrder: triggerOrder(1),
+          escrow: escrow(1),
+          ownerTokenAccount,
+          owner: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc();
+
+      expect((await balance(ownerTokenAccount)) - before).to.equal(11 * UNIT);
+      expect(
+        await program.account.triggerOrder.fetchNullable(triggerOrder(1))
+      ).to.be.null;
+    });
+  });
+
+  describe("rfq swaps", () => {
+    const maker = Keypair.generate();
+
+    const makerNonces = pda(
+      Buffer.from("maker_nonces"),
+      maker.publicKey.toBuffer(),
+      new BN(0).toArrayLike(Buffer, "le", 8)
+    );
+    const rfqAuthority = pda(Buffer.from("rfq_authority"));
+
+    const quoteFor = (amountIn: number, amountOut: number, nonce: number) => ({
+      maker: maker.publicKey,
+      taker: PublicKey.default,
+      inputMint: fixture.mintOne,
+      outputMint: fixture.mintTwo,
+      amountIn: new BN(amountIn),
+      amountOut: new BN(amountOut),
+      expiry: new BN(Math.floor(Date.now() / 1000) + 60),
+      nonce: new BN(nonce),
+    });
+
+    // Makers sign the quote bound to this program, verified by the Ed25519
+    // program right before the swap
+    const signQuote = (
+      quote: ReturnType<typeof quoteFor>,
+      signer: Keypair = maker
+    ) =>
+      Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message: Buffer.concat([
+          program.programId.toBuffer(),
+          program.coder.types.encode("RfqQuote", quote),
+        ]),
+      });
+
+    const rfqSwap = (
+      amountIn: number,
+      quote: ReturnType<typeof quoteFor> | null,
+      signer: Keypair = maker
+    ) =>
+      program.methods
+        .rfqSwap(new BN(amountIn), new BN(0), quote)
+        .accounts({
+          dexConfigurationAccount: dexConfiguration,
+          pool: fixture.pool,
+          mintTokenOne: fixture.mintOne,
+          mintTokenTwo: fixture.mintTwo,
+          poolTokenAccountOne: fixture.poolTokenAccountOne,
+          poolTokenAccountTwo: fixture.poolTokenAccountTwo,
+          takerTokenAccountOne: ata(fixture.mintOne, user.publicKey),
+          takerTokenAccountTwo: ata(fixture.mintTwo, user.publicKey),
+          makerTokenAccountOne: quote
+            ? ata(fixture.mintOne, maker.publicKey)
+            : null,
+          makerTokenAccountTwo: quote
+            ? ata(fixture.mintTwo, maker.publicKey)
+            : null,
+          makerNonces: quote ? makerNonces : null,
+          rfqAuthority,
+          instructionsSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+          taker: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .preInstructions(quote ? [signQuote(quote, signer)] : [])
+        .signers([user]);
+
+    before(async () => {
+      await airdrop(maker.publicKey);
+      await fundWallet(fixture, maker.publicKey);
+
+      // The maker lets the program settle its quotes from its token account
+      await approve(
+        connection,
+        admin,
+        ata(fixture.mintTwo, maker.publicKey),
+        rfqAuthority,
+        maker,
+        100 * UNIT
+      );
+    });
+
+    it("registers a page of maker nonces", async () => {
+      await program.methods
+        .registerRfqMaker(new BN(0))
+        .accounts({
+          makerNonces,
+          maker: maker.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([maker])
+        .rpc();
+
+      const account = await program.account.makerNonces.fetch(makerNonces);
+      expect(account.maker.equals(maker.publicKey)).to.be.true;
+      expect(account.page.toNumber()).to.equal(0);
+    });
+
+    it("routes through the pool without a quote", async () => {
+      const result = await returnData(rfqSwap(UNIT, null), "RfqSwapAmounts");
+      expect(result.filledByQuote).to.be.false;
+
+      const takerTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const before = await balance(takerTokenAccount);
+
+      await rfqSwap(UNIT, null).rpc();
+
+      expect(await balance(takerTokenAccount)).to.be.greaterThan(before);
+    });
+
+    it("fills a signed quote at the quoted price", async () => {
+      const quote = quoteFor(10 * UNIT, 11 * UNIT, 0);
+      const takerTokenAccount = ata(fixture.mintTwo, user.publicKey);
+      const makerTokenAccount = ata(fixture.mintOne, maker.publicKey);
+      const takerBefore = await balance(takerTokenAccount);
+      const makerBefore = await balance(makerTokenAccount);
+
+      const result = await returnData(
+        rfqSwap(10 * UNIT, quote),
+        "RfqSwapAmounts"
+      );
+      expect(result.filledByQuote).to.be.true;
+
+      await rfqSwap(10 * UNIT, quote).rpc();
+
+      expect((await balance(takerTokenAccount)) - takerBefore).to.equal(
+        11 * UNIT
+      );
+      expect((await balance(makerTokenAccount)) - makerBefore).to.equal(
+        10 * UNIT
+      );
+
+      const account = await program.account.makerNonces.fetch(makerNonces);
+      expect(account.bitmap[0].toNumber()).to.equal(1);
+    });
 
-    await program.methods.initializeLiquidityPool().accounts({}).rpc();
+    it("falls back to the pool once a quote was filled", async () => {
+      const quote = quoteFor(10 * UNIT, 11 * UNIT, 0);
 
-    let pool = await program.account.liquidityPool.fetch(poolPda);
+      const result = await returnData(
+        rfqSwap(10 * UNIT, quote),
+        "RfqSwapAmounts"
+      );
+      expect(result.filledByQuote).to.be.false;
+    });
 
-    expect(pool.assets.length).to.equal(0);
-    expect(pool.bump).not.null;
+    it("rejects quotes the maker did not sign", async () => {
+      await expectError(
+        rfqSwap(10 * UNIT, quoteFor(10 * UNIT, 11 * UNIT, 1), user).rpc(),
+        "InvalidQuoteSignature"
+      );
+      await expectError(
+        rfqSwap(5 * UNIT, quoteFor(10 * UNIT, 11 * UNIT, 1)).rpc(),
+        "InvalidRfqQuote"
+      );
+    });
   });
 });